@@ -0,0 +1,3 @@
+pub mod models;
+pub mod storage_engine;
+pub mod utils;
@@ -1,25 +1,132 @@
 use core::mem::size_of;
-use std::convert::TryInto;
 
-use crate::utils::encoding::endian::endian::{write_bytes, write_u16_le, write_u64_le, read_u64_le};
+use crate::storage_engine::engine::CorruptedFileError;
+use crate::utils::checksum::crc32c::crc32c;
+use crate::utils::encoding::endian::endian::{
+    read_bytes, read_u16_le, read_u64_le, read_u8, write_bytes, write_u16_le, write_u64_le,
+};
 
 pub const FILE_HEADER_MAGIC: [u8; 6] = *b"NXRv0\0";
+/// Files of a newer major version cannot be read; newer minor versions stay readable.
+pub const FILE_FORMAT_MAJOR: u8 = 0;
+pub const FILE_FORMAT_MINOR: u8 = 3;
+/// Version of the on-disk format written by this build.
+pub const FILE_FORMAT_VERSION: u16 = format_version(FILE_FORMAT_MAJOR, FILE_FORMAT_MINOR);
+/// First version with alternating header slots and footer pages.
+pub const HEADER_SLOTS_VERSION: u16 = format_version(0, 2);
+/// First version whose node and edge sections are indexed by id.
+pub const ID_INDEX_VERSION: u16 = format_version(0, 3);
+/// Offsets of the two header slots.
+pub const HEADER_SLOT_OFFSETS: [u64; 2] = [0, PAGE_SIZE as u64];
 pub const PROPERTY_NAME_MAX_SIZE: usize = 55;
 pub const MAX_PROPERTIES_COUNT: usize = 120;
 pub const PAGE_SIZE: usize = 4096;
 pub const KB1: usize = 1024;
 pub const INVALID_OFFSET: u64 = u64::MAX;
+/// Bytes at the end of every record page that are kept free for page-level metadata.
+pub const PAGE_TRAILER_SIZE: usize = 64;
+/// Header flag set on files whose pages all carry a checksum.
+pub const HEADER_FLAG_CHECKSUMS: u16 = 1 << 0;
+/// Every page layout keeps its last bytes reserved; the CRC32C of the rest of the page lives there.
+pub const PAGE_CHECKSUM_OFFSET: usize = PAGE_SIZE - 4;
+/// Marks a page sitting on the free page list, followed by the offset of the next one.
+pub const FREE_PAGE_MARKER: u32 = u32::from_le_bytes(*b"NXFP");
+/// Number of `Name` slots packed into a single name page.
+pub const NAMES_PER_PAGE: usize = (PAGE_SIZE - PAGE_TRAILER_SIZE) / size_of::<Name>();
+
+/// Packs a format version the way `NexoraHeader::version` stores it: major in the high byte.
+pub const fn format_version(major: u8, minor: u8) -> u16 {
+    ((major as u16) << 8) | minor as u16
+}
+
+/// -------------------- Page checksum --------------------
+fn page_checksum(page: &[u8; PAGE_SIZE]) -> u32 {
+    crc32c(&page[..PAGE_CHECKSUM_OFFSET])
+}
+
+/// Stores the checksum of a page in its last four bytes.
+pub fn stamp_page_checksum(page: &mut [u8; PAGE_SIZE]) {
+    let checksum = page_checksum(page);
+    page[PAGE_CHECKSUM_OFFSET..].copy_from_slice(&checksum.to_le_bytes());
+}
+
+/// Whether the checksum stored in the last four bytes of a page matches its content.
+pub fn verify_page_checksum(page: &[u8; PAGE_SIZE]) -> bool {
+    page[PAGE_CHECKSUM_OFFSET..] == page_checksum(page).to_le_bytes()
+}
+
+const _: () = assert!(PAGE_TRAILER_SIZE >= PAGE_SIZE - PAGE_CHECKSUM_OFFSET);
+
+/// -------------------- Field reader --------------------
+/// Reads consecutive little-endian fields of an on-disk struct, failing instead of
+/// panicking when the buffer ends early.
+struct FieldReader<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FieldReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, offset: 0 }
+    }
+
+    fn u8(&mut self) -> Result<u8, CorruptedFileError> {
+        let value = read_u8(self.buf, self.offset).ok_or(CorruptedFileError::Truncated)?;
+        self.offset += 1;
+        Ok(value)
+    }
+
+    fn u16(&mut self) -> Result<u16, CorruptedFileError> {
+        let value = read_u16_le(self.buf, self.offset).ok_or(CorruptedFileError::Truncated)?;
+        self.offset += 2;
+        Ok(value)
+    }
+
+    fn u64(&mut self) -> Result<u64, CorruptedFileError> {
+        let value = read_u64_le(self.buf, self.offset).ok_or(CorruptedFileError::Truncated)?;
+        self.offset += 8;
+        Ok(value)
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], CorruptedFileError> {
+        let mut value = [0u8; N];
+        value.copy_from_slice(read_bytes(self.buf, self.offset, N).ok_or(CorruptedFileError::Truncated)?);
+        self.offset += N;
+        Ok(value)
+    }
+
+    fn u64_array<const N: usize>(&mut self) -> Result<[u64; N], CorruptedFileError> {
+        let mut values = [0u64; N];
+        for value in &mut values {
+            *value = self.u64()?;
+        }
+        Ok(values)
+    }
+}
 
 /// -------------------- Header --------------------
+/// Since format 0.2 the header lives in two alternating slots. Each commit writes the header
+/// to the slot not holding the current one, with the next generation, so switching to the
+/// new root is a single page write and the previous header stays intact until it succeeds.
+/// Loading picks the valid header of the highest generation.
+///
+/// Fields are written in declaration order except for `generation`, `end_offset` and
+/// `previous_footer_offset`, which follow `flags` in what older versions reserved.
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct NexoraHeader {
-    pub footer_offset: u64,    // 8
-    pub created_unix: u64,     // 8
-    pub magic: [u8; 6],        // 6
-    pub version: u16,          // 2
-    pub flags: u16,            // 2
-    pub _reserved: [u8; 4070], // 4070 + 8+8+6+2+2 = 4096
+    pub footer_offset: u64,          // 8
+    pub created_unix: u64,           // 8
+    /// Incremented on every root switch; selects the live header slot.
+    pub generation: u64,             // 8
+    /// Offset just past the last allocated page.
+    pub end_offset: u64,             // 8
+    /// The page holding the previous footer, where the next one is written.
+    pub previous_footer_offset: u64, // 8
+    pub magic: [u8; 6],              // 6
+    pub version: u16,                // 2
+    pub flags: u16,                  // 2
+    pub _reserved: [u8; 4046],       // 4046 + 8+8+8+8+8+6+2+2 = 4096
 }
 
 impl Default for NexoraHeader {
@@ -27,10 +134,13 @@ impl Default for NexoraHeader {
         Self {
             footer_offset: INVALID_OFFSET,
             created_unix: 0,
+            generation: 0,
+            end_offset: INVALID_OFFSET,
+            previous_footer_offset: INVALID_OFFSET,
             magic: FILE_HEADER_MAGIC,
-            version: 0,
-            flags: 0,
-            _reserved: [0u8; 4070],
+            version: FILE_FORMAT_VERSION,
+            flags: HEADER_FLAG_CHECKSUMS,
+            _reserved: [0u8; 4046],
         }
     }
 }
@@ -40,39 +150,113 @@ impl NexoraHeader {
         raw_magic == FILE_HEADER_MAGIC
     }
 
-    pub fn deserialize(raw_header_data: [u8; PAGE_SIZE]) -> Self {
-        let mut offset = 0;
+    pub fn major_version(&self) -> u8 {
+        (self.version >> 8) as u8
+    }
 
-        // helper macro to grab slices safely
-        macro_rules! take {
-            ($len:expr) => {{
-                let start = offset;
-                let end = offset + $len;
-                offset = end;
-                &raw_header_data[start..end]
-            }};
+    pub fn minor_version(&self) -> u8 {
+        self.version as u8
+    }
+
+    /// Whether every page of the file carries a checksum. Files written before checksums
+    /// existed lack the flag and are read without verification.
+    pub fn has_checksums(&self) -> bool {
+        self.flags & HEADER_FLAG_CHECKSUMS != 0
+    }
+
+    /// Whether the file alternates between two header slots and two footer pages.
+    /// Older files keep a single header on page 0 and rewrite the footer in place.
+    pub fn has_header_slots(&self) -> bool {
+        self.version >= HEADER_SLOTS_VERSION
+    }
+
+    /// Offset of the slot the header of `generation` is written to.
+    pub fn slot_offset(generation: u64) -> u64 {
+        HEADER_SLOT_OFFSETS[(generation % HEADER_SLOT_OFFSETS.len() as u64) as usize]
+    }
+
+    /// Parses a header page, rejecting foreign files and major versions this build cannot read.
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
+
+        let footer_offset = reader.u64()?;
+        let created_unix = reader.u64()?;
+        let magic = reader.bytes()?;
+        if !Self::verify_magic(magic) {
+            return Err(CorruptedFileError::InvalidMagicValue);
         }
 
-        let footer_offset = u64::from_le_bytes(take!(8).try_into().unwrap());
-        let created_unix = u64::from_le_bytes(take!(8).try_into().unwrap());
+        let version = reader.u16()?;
+        if (version >> 8) as u8 > FILE_FORMAT_MAJOR {
+            return Err(CorruptedFileError::UnsupportedVersion(version));
+        }
 
-        let mut magic = [0u8; 6];
-        magic.copy_from_slice(take!(6));
+        let flags = reader.u16()?;
+        let mut generation = reader.u64()?;
+        let mut end_offset = reader.u64()?;
+        let mut previous_footer_offset = reader.u64()?;
+        let reserved = reader.bytes()?;
+        debug_assert_eq!(reader.offset, PAGE_SIZE);
 
-        let version = u16::from_le_bytes(take!(2).try_into().unwrap());
-        let flags = u16::from_le_bytes(take!(2).try_into().unwrap());
+        if footer_offset == INVALID_OFFSET || !footer_offset.is_multiple_of(PAGE_SIZE as u64) {
+            return Err(CorruptedFileError::InvalidOffsetValue);
+        }
 
-        let mut reserved = [0u8; 4070];
-        reserved.copy_from_slice(take!(4070));
+        // Single-slot files keep their footer on the last page and have no generations.
+        if version < HEADER_SLOTS_VERSION {
+            generation = 0;
+            end_offset = footer_offset
+                .checked_add(PAGE_SIZE as u64)
+                .ok_or(CorruptedFileError::InvalidOffsetValue)?;
+            previous_footer_offset = INVALID_OFFSET;
+        } else if !end_offset.is_multiple_of(PAGE_SIZE as u64)
+            || footer_offset >= end_offset
+            || previous_footer_offset >= end_offset
+            || !previous_footer_offset.is_multiple_of(PAGE_SIZE as u64)
+            || previous_footer_offset == footer_offset
+        {
+            return Err(CorruptedFileError::InvalidOffsetValue);
+        }
 
-        Self {
+        Ok(Self {
             footer_offset,
             created_unix,
+            generation,
+            end_offset,
+            previous_footer_offset,
             magic,
             version,
             flags,
             _reserved: reserved,
+        })
+    }
+
+    pub fn serialize(&self) -> [u8; PAGE_SIZE] {
+        let mut buf = [0u8; PAGE_SIZE];
+        let mut offset = 0;
+
+        write_u64_le(self.footer_offset, &mut buf[offset..offset + 8]);
+        offset += 8;
+        write_u64_le(self.created_unix, &mut buf[offset..offset + 8]);
+        offset += 8;
+        write_bytes(&self.magic, &mut buf[offset..offset + self.magic.len()]);
+        offset += self.magic.len();
+        write_u16_le(self.version, &mut buf[offset..offset + 2]);
+        offset += 2;
+        write_u16_le(self.flags, &mut buf[offset..offset + 2]);
+        offset += 2;
+        if self.has_header_slots() {
+            write_u64_le(self.generation, &mut buf[offset..offset + 8]);
+            write_u64_le(self.end_offset, &mut buf[offset + 8..offset + 16]);
+            write_u64_le(self.previous_footer_offset, &mut buf[offset + 16..offset + 24]);
         }
+        offset += 24;
+        write_bytes(&self._reserved, &mut buf[offset..offset + self._reserved.len()]);
+        offset += self._reserved.len();
+
+        assert_eq!(offset, PAGE_SIZE, "NexoraHeader serialization size mismatch");
+
+        buf
     }
 }
 
@@ -88,6 +272,34 @@ pub struct OffsetMetadataTable {
 }
 const _: () = assert!(size_of::<OffsetMetadataTable>() == 16);
 
+/// -------------------- Section --------------------
+/// Identifies one of the eight offset tables referenced by the footer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    NameTable,
+    NodeSchema,
+    EdgeSchema,
+    SchemaProperties,
+    Metadata,
+    Indices,
+    Nodes,
+    Edges,
+}
+
+impl Section {
+    /// Every section, in the order of `NexoraFooter::tables`.
+    pub const ALL: [Section; 8] = [
+        Section::NameTable,
+        Section::NodeSchema,
+        Section::EdgeSchema,
+        Section::SchemaProperties,
+        Section::Metadata,
+        Section::Indices,
+        Section::Nodes,
+        Section::Edges,
+    ];
+}
+
 /// -------------------- Footer --------------------
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -100,7 +312,18 @@ pub struct NexoraFooter {
     pub indices_offset: OffsetMetadataTable,
     pub nodes_offset: OffsetMetadataTable,
     pub edges_offset: OffsetMetadataTable,
-    pub _reserved: [u8; 3968],
+    /// Head of the free cell list of each heap class, 0 when the list is empty.
+    pub heap_free_lists: [u64; HEAP_CLASS_COUNT],
+    /// Head of the list of freed pages, 0 when the list is empty.
+    pub free_page_head: u64,
+    pub nb_free_pages: u64,
+    /// Pages the file was extended by that were never handed out, ending at `header.end_offset`.
+    pub nb_reserved_pages: u64,
+    /// Last chunk of each section, indexed like `tables`, so appends skip the chain walk.
+    pub tail_chunk_offsets: [u64; 8],
+    /// Head of the list of free node and edge record slots, 0 when the list is empty.
+    pub record_free_list: u64,
+    pub _reserved: [u8; 3840],
 }
 
 impl Default for NexoraFooter {
@@ -114,48 +337,107 @@ impl Default for NexoraFooter {
             indices_offset: OffsetMetadataTable::default(),
             nodes_offset: OffsetMetadataTable::default(),
             edges_offset: OffsetMetadataTable::default(),
-            _reserved: [0u8; 3968],
+            heap_free_lists: [0u64; HEAP_CLASS_COUNT],
+            free_page_head: 0,
+            nb_free_pages: 0,
+            nb_reserved_pages: 0,
+            tail_chunk_offsets: [0u64; 8],
+            record_free_list: 0,
+            _reserved: [0u8; 3840],
         }
     }
 }
 
 impl NexoraFooter {
-    pub fn deserialize(raw_footer_data: [u8; PAGE_SIZE]) -> Self {
-        let mut offset = 0;
+    /// The eight section tables, in on-disk order.
+    pub fn tables(&self) -> [&OffsetMetadataTable; 8] {
+        [
+            &self.name_table_offset,
+            &self.node_schema_offset,
+            &self.edge_schema_offset,
+            &self.schema_properties_offset,
+            &self.metadata_offset,
+            &self.indices_offset,
+            &self.nodes_offset,
+            &self.edges_offset,
+        ]
+    }
 
-        // helper macro to grab slices safely
-        macro_rules! take {
-            ($len:expr) => {{
-                let start = offset;
-                let end = offset + $len;
-                offset = end;
-                &raw_footer_data[start..end]
-            }};
+    /// Offset of the last chunk of a section's chain.
+    ///
+    /// Files written before tails were recorded hold 0 here; the engine fills the tails in
+    /// while validating the chains on load.
+    pub fn tail_chunk(&self, section: Section) -> u64 {
+        self.tail_chunk_offsets[section as usize]
+    }
+
+    pub fn set_tail_chunk(&mut self, section: Section, offset: u64) {
+        self.tail_chunk_offsets[section as usize] = offset;
+    }
+
+    pub fn table(&self, section: Section) -> &OffsetMetadataTable {
+        match section {
+            Section::NameTable => &self.name_table_offset,
+            Section::NodeSchema => &self.node_schema_offset,
+            Section::EdgeSchema => &self.edge_schema_offset,
+            Section::SchemaProperties => &self.schema_properties_offset,
+            Section::Metadata => &self.metadata_offset,
+            Section::Indices => &self.indices_offset,
+            Section::Nodes => &self.nodes_offset,
+            Section::Edges => &self.edges_offset,
         }
+    }
 
-        fn parse_offset_table(bytes: &[u8]) -> OffsetMetadataTable {
-            use std::convert::TryInto;
-            let nb_total_items = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
-            let base_chunk_offset = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
-            OffsetMetadataTable {
-                nb_total_items,
-                base_chunk_offset,
-            }
+    pub fn table_mut(&mut self, section: Section) -> &mut OffsetMetadataTable {
+        match section {
+            Section::NameTable => &mut self.name_table_offset,
+            Section::NodeSchema => &mut self.node_schema_offset,
+            Section::EdgeSchema => &mut self.edge_schema_offset,
+            Section::SchemaProperties => &mut self.schema_properties_offset,
+            Section::Metadata => &mut self.metadata_offset,
+            Section::Indices => &mut self.indices_offset,
+            Section::Nodes => &mut self.nodes_offset,
+            Section::Edges => &mut self.edges_offset,
         }
+    }
 
-        let name_table_offset = parse_offset_table(take!(16));
-        let node_schema_offset = parse_offset_table(take!(16));
-        let edge_schema_offset = parse_offset_table(take!(16));
-        let schema_properties_offset = parse_offset_table(take!(16));
-        let metadata_offset = parse_offset_table(take!(16));
-        let indices_offset = parse_offset_table(take!(16));
-        let nodes_offset = parse_offset_table(take!(16));
-        let edges_offset = parse_offset_table(take!(16));
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
 
-        let mut reserved = [0u8; 3968];
-        reserved.copy_from_slice(take!(3968));
+        let mut parse_offset_table = || -> Result<OffsetMetadataTable, CorruptedFileError> {
+            let table = OffsetMetadataTable {
+                nb_total_items: reader.u64()?,
+                base_chunk_offset: reader.u64()?,
+            };
+            if table.base_chunk_offset == INVALID_OFFSET || !table.base_chunk_offset.is_multiple_of(PAGE_SIZE as u64) {
+                return Err(CorruptedFileError::InvalidOffsetValue);
+            }
+            Ok(table)
+        };
 
-        Self {
+        let name_table_offset = parse_offset_table()?;
+        let node_schema_offset = parse_offset_table()?;
+        let edge_schema_offset = parse_offset_table()?;
+        let schema_properties_offset = parse_offset_table()?;
+        let metadata_offset = parse_offset_table()?;
+        let indices_offset = parse_offset_table()?;
+        let nodes_offset = parse_offset_table()?;
+        let edges_offset = parse_offset_table()?;
+
+        let heap_free_lists = reader.u64_array()?;
+        let free_page_head = reader.u64()?;
+        let nb_free_pages = reader.u64()?;
+        let nb_reserved_pages = reader.u64()?;
+        let tail_chunk_offsets = reader.u64_array()?;
+        let record_free_list = reader.u64()?;
+        let reserved = reader.bytes()?;
+        debug_assert_eq!(reader.offset, PAGE_SIZE);
+
+        if !free_page_head.is_multiple_of(PAGE_SIZE as u64) || !record_free_list.is_multiple_of(KB1 as u64) {
+            return Err(CorruptedFileError::InvalidOffsetValue);
+        }
+
+        Ok(Self {
             name_table_offset,
             node_schema_offset,
             edge_schema_offset,
@@ -164,8 +446,14 @@ impl NexoraFooter {
             indices_offset,
             nodes_offset,
             edges_offset,
+            heap_free_lists,
+            free_page_head,
+            nb_free_pages,
+            nb_reserved_pages,
+            tail_chunk_offsets,
+            record_free_list,
             _reserved: reserved,
-        }
+        })
     }
 
     pub fn serialize(&self) -> [u8; PAGE_SIZE] {
@@ -189,6 +477,21 @@ impl NexoraFooter {
         write_offset_table(&self.nodes_offset, &mut buf, &mut offset);
         write_offset_table(&self.edges_offset, &mut buf, &mut offset);
 
+        for head in &self.heap_free_lists {
+            write_u64_le(*head, &mut buf[offset..offset + 8]);
+            offset += 8;
+        }
+        for value in [self.free_page_head, self.nb_free_pages, self.nb_reserved_pages] {
+            write_u64_le(value, &mut buf[offset..offset + 8]);
+            offset += 8;
+        }
+        for tail in &self.tail_chunk_offsets {
+            write_u64_le(*tail, &mut buf[offset..offset + 8]);
+            offset += 8;
+        }
+        write_u64_le(self.record_free_list, &mut buf[offset..offset + 8]);
+        offset += 8;
+
         // Write reserved
         buf[offset..offset + self._reserved.len()].copy_from_slice(&self._reserved);
         offset += self._reserved.len();
@@ -222,6 +525,22 @@ impl Default for OffsetItem {
 const _: () = assert!(size_of::<OffsetItem>() == 16);
 
 /// -------------------- OffsetTableChunk --------------------
+/// Number of `OffsetItem` slots in a single chunk.
+pub const OFFSET_ITEMS_PER_CHUNK: usize = 254;
+
+/// Position of the item at `index` of the chunk at `chunk_offset`. Chunks are page aligned,
+/// so the index fits in the low bits of the offset.
+pub const fn offset_item_position(chunk_offset: u64, index: usize) -> u64 {
+    chunk_offset | index as u64
+}
+
+/// Splits a position made by [`offset_item_position`] back into chunk offset and index.
+pub const fn split_offset_item_position(position: u64) -> (u64, usize) {
+    let index = position % PAGE_SIZE as u64;
+    (position - index, index as usize)
+}
+const _: () = assert!(OFFSET_ITEMS_PER_CHUNK < PAGE_SIZE);
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct OffsetTableChunk {
@@ -229,7 +548,7 @@ pub struct OffsetTableChunk {
     pub _pad0: [u8; 7],
     pub previous_chunk: u64,
     pub next_chunk: u64,
-    pub offset_items: [OffsetItem; 254],
+    pub offset_items: [OffsetItem; OFFSET_ITEMS_PER_CHUNK],
     pub _reserved: [u8; 8],
 }
 
@@ -240,7 +559,7 @@ impl Default for OffsetTableChunk {
             _pad0: [0u8; 7],
             previous_chunk: INVALID_OFFSET,
             next_chunk: INVALID_OFFSET,
-            offset_items: [OffsetItem::default(); 254],
+            offset_items: [OffsetItem::default(); OFFSET_ITEMS_PER_CHUNK],
             _reserved: [0u8; 8],
         }
     }
@@ -284,57 +603,40 @@ impl OffsetTableChunk {
         buf
     }
 
-    pub fn deserialize(buf: &[u8; PAGE_SIZE]) -> Self {
-        let mut offset = 0;
-
-        // ---- nb_items ----
-        let nb_items = buf[offset];
-        offset += 1;
+    /// Parses a chunk, rejecting item counts larger than the chunk can hold.
+    ///
+    /// Links to other chunks are not checked here since the chunk does not know its own offset.
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
 
-        // ---- pad0 ----
-        let mut pad0 = [0u8; 7];
-        pad0.copy_from_slice(&buf[offset..offset + 7]);
-        offset += 7;
-
-        // ---- previous_chunk ----
-        let previous_chunk = read_u64_le(buf, offset).unwrap();
-        offset += 8;
+        let nb_items = reader.u8()?;
+        if nb_items as usize > OFFSET_ITEMS_PER_CHUNK {
+            return Err(CorruptedFileError::TooManyOffsetItems(nb_items));
+        }
 
-        // ---- next_chunk ----
-        let next_chunk = read_u64_le(buf, offset).unwrap();
-        offset += 8;
+        let pad0 = reader.bytes()?;
+        let previous_chunk = reader.u64()?;
+        let next_chunk = reader.u64()?;
 
-        // ---- offset_items ----
-        let mut offset_items = [OffsetItem::default(); 254];
+        let mut offset_items = [OffsetItem::default(); OFFSET_ITEMS_PER_CHUNK];
         for item in &mut offset_items {
-            let id = read_u64_le(buf, offset).unwrap();
-            offset += 8;
-
-            let item_offset = read_u64_le(buf, offset).unwrap();
-            offset += 8;
-
-            *item = OffsetItem { id, offset: item_offset };
+            *item = OffsetItem {
+                id: reader.u64()?,
+                offset: reader.u64()?,
+            };
         }
 
-        // ---- reserved ----
-        let mut reserved = [0u8; 8];
-        reserved.copy_from_slice(&buf[offset..offset + 8]);
-        offset += 8;
-
-        assert_eq!(
-            offset,
-            PAGE_SIZE,
-            "OffsetTableChunk deserialization did not consume full buffer"
-        );
+        let reserved = reader.bytes()?;
+        debug_assert_eq!(reader.offset, PAGE_SIZE);
 
-        Self {
+        Ok(Self {
             nb_items,
             _pad0: pad0,
             previous_chunk,
             next_chunk,
             offset_items,
             _reserved: reserved,
-        }
+        })
     }
 }
 
@@ -358,9 +660,45 @@ impl Default for Name {
 }
 const _: () = assert!(size_of::<Name>() == 64);
 
+impl Name {
+    pub fn serialize(&self) -> [u8; 64] {
+        let mut buf = [0u8; 64];
+        let mut offset = 0;
+
+        write_u64_le(self.id, &mut buf[offset..offset + 8]);
+        offset += 8;
+        buf[offset] = self.size;
+        offset += 1;
+        write_bytes(&self.value, &mut buf[offset..offset + PROPERTY_NAME_MAX_SIZE]);
+        offset += PROPERTY_NAME_MAX_SIZE;
+
+        assert_eq!(offset, 64, "Name serialization size mismatch");
+
+        buf
+    }
+
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
+
+        let id = reader.u64()?;
+        let size = reader.u8()?;
+        if size as usize > PROPERTY_NAME_MAX_SIZE {
+            return Err(CorruptedFileError::InvalidNameLength(size));
+        }
+        let value = reader.bytes()?;
+
+        Ok(Self { id, size, value })
+    }
+
+    /// Returns the stored bytes of the name, without the zero padding.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.value[..(self.size as usize).min(PROPERTY_NAME_MAX_SIZE)]
+    }
+}
+
 /// -------------------- PropertyType --------------------
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum PropertyType {
     Int8 = 0,
     Int16,
@@ -375,12 +713,31 @@ pub enum PropertyType {
     String512,
     Page,
     Bool,
+    #[default]
     InvalidType,
 }
 
-impl Default for PropertyType {
-    fn default() -> Self {
-        PropertyType::InvalidType
+impl TryFrom<u8> for PropertyType {
+    type Error = CorruptedFileError;
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        Ok(match raw {
+            0 => PropertyType::Int8,
+            1 => PropertyType::Int16,
+            2 => PropertyType::Int32,
+            3 => PropertyType::Int64,
+            4 => PropertyType::Float8,
+            5 => PropertyType::Float16,
+            6 => PropertyType::Float32,
+            7 => PropertyType::Float64,
+            8 => PropertyType::String32,
+            9 => PropertyType::String64,
+            10 => PropertyType::String512,
+            11 => PropertyType::Page,
+            12 => PropertyType::Bool,
+            13 => PropertyType::InvalidType,
+            _ => return Err(CorruptedFileError::UnknownPropertyType(raw)),
+        })
     }
 }
 
@@ -389,7 +746,7 @@ impl Default for PropertyType {
 #[derive(Debug, Clone, Copy)]
 pub struct PropertyDefinition {
     pub name_id: u64,
-    pub r#type: u8,
+    pub r#type: PropertyType,
     pub optional: u8,
     pub _reserved: [u8; 6],
 }
@@ -398,7 +755,7 @@ impl Default for PropertyDefinition {
     fn default() -> Self {
         Self {
             name_id: 0,
-            r#type: PropertyType::InvalidType as u8,
+            r#type: PropertyType::InvalidType,
             optional: 0,
             _reserved: [0u8; 6],
         }
@@ -406,6 +763,28 @@ impl Default for PropertyDefinition {
 }
 const _: () = assert!(size_of::<PropertyDefinition>() == 16);
 
+impl PropertyDefinition {
+    pub fn serialize(&self) -> [u8; 16] {
+        let mut buf = [0u8; 16];
+        write_u64_le(self.name_id, &mut buf[0..8]);
+        buf[8] = self.r#type as u8;
+        buf[9] = self.optional;
+        write_bytes(&self._reserved, &mut buf[10..16]);
+        buf
+    }
+
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
+
+        Ok(Self {
+            name_id: reader.u64()?,
+            r#type: PropertyType::try_from(reader.u8()?)?,
+            optional: reader.u8()?,
+            _reserved: reader.bytes()?,
+        })
+    }
+}
+
 /// -------------------- NodeSchema --------------------
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -430,6 +809,53 @@ impl Default for NodeSchema {
 }
 const _: () = assert!(size_of::<NodeSchema>() == KB1);
 
+impl NodeSchema {
+    pub fn serialize(&self) -> [u8; KB1] {
+        let mut buf = [0u8; KB1];
+        let mut offset = 0;
+
+        write_u64_le(self.id, &mut buf[offset..offset + 8]);
+        offset += 8;
+        write_u16_le(self.property_count, &mut buf[offset..offset + 2]);
+        offset += 2;
+        write_bytes(&self._pad, &mut buf[offset..offset + self._pad.len()]);
+        offset += self._pad.len();
+        for property in &self.properties {
+            write_u64_le(*property, &mut buf[offset..offset + 8]);
+            offset += 8;
+        }
+        write_bytes(&self._reserved, &mut buf[offset..offset + self._reserved.len()]);
+        offset += self._reserved.len();
+
+        assert_eq!(offset, KB1, "NodeSchema serialization size mismatch");
+
+        buf
+    }
+
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
+
+        let id = reader.u64()?;
+        let property_count = reader.u16()?;
+        if property_count as usize > MAX_PROPERTIES_COUNT {
+            return Err(CorruptedFileError::InvalidPropertyCount(property_count));
+        }
+
+        let pad = reader.bytes()?;
+        let properties = reader.u64_array()?;
+        let reserved = reader.bytes()?;
+        debug_assert_eq!(reader.offset, KB1);
+
+        Ok(Self {
+            id,
+            property_count,
+            _pad: pad,
+            properties,
+            _reserved: reserved,
+        })
+    }
+}
+
 /// -------------------- EdgeSchema --------------------
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -454,6 +880,96 @@ impl Default for EdgeSchema {
 }
 const _: () = assert!(size_of::<EdgeSchema>() == KB1);
 
+impl EdgeSchema {
+    pub fn serialize(&self) -> [u8; KB1] {
+        let mut buf = [0u8; KB1];
+        let mut offset = 0;
+
+        write_u64_le(self.id, &mut buf[offset..offset + 8]);
+        offset += 8;
+        write_u16_le(self.property_count, &mut buf[offset..offset + 2]);
+        offset += 2;
+        write_bytes(&self._pad, &mut buf[offset..offset + self._pad.len()]);
+        offset += self._pad.len();
+        for property in &self.properties {
+            write_u64_le(*property, &mut buf[offset..offset + 8]);
+            offset += 8;
+        }
+        write_bytes(&self._reserved, &mut buf[offset..offset + self._reserved.len()]);
+        offset += self._reserved.len();
+
+        assert_eq!(offset, KB1, "EdgeSchema serialization size mismatch");
+
+        buf
+    }
+
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
+
+        let id = reader.u64()?;
+        let property_count = reader.u16()?;
+        if property_count as usize > MAX_PROPERTIES_COUNT {
+            return Err(CorruptedFileError::InvalidPropertyCount(property_count));
+        }
+
+        let pad = reader.bytes()?;
+        let properties = reader.u64_array()?;
+        let reserved = reader.bytes()?;
+        debug_assert_eq!(reader.offset, KB1);
+
+        Ok(Self {
+            id,
+            property_count,
+            _pad: pad,
+            properties,
+            _reserved: reserved,
+        })
+    }
+}
+
+/// -------------------- Heap --------------------
+/// Variable-length property values live in heap cells referenced by offset from their slot.
+/// A cell starts with its byte length (`u32`), the class it was carved for (`u8`) and 3
+/// reserved bytes, followed by the value.
+pub const HEAP_CLASS_COUNT: usize = 4;
+pub const HEAP_CELL_HEADER_SIZE: usize = 8;
+/// Position of the class byte within a cell header.
+pub const HEAP_CELL_CLASS_OFFSET: usize = 4;
+/// Length marker of a cell on a free list; the next free cell follows the header.
+pub const HEAP_FREE_CELL: u32 = u32::MAX;
+
+/// -------------------- Property bitmap --------------------
+/// One bit per property slot telling whether the slot holds a value.
+pub const PROPERTY_BITMAP_SIZE: usize = 16;
+const _: () = assert!(PROPERTY_BITMAP_SIZE * 8 >= MAX_PROPERTIES_COUNT);
+
+pub(crate) fn bitmap_get(bitmap: &[u8; PROPERTY_BITMAP_SIZE], index: usize) -> bool {
+    bitmap[index / 8] & (1 << (index % 8)) != 0
+}
+
+/// Reads a presence bitmap, rejecting bits set past the last property slot.
+fn read_property_bitmap(reader: &mut FieldReader) -> Result<[u8; PROPERTY_BITMAP_SIZE], CorruptedFileError> {
+    let bitmap = reader.bytes()?;
+    if (MAX_PROPERTIES_COUNT..PROPERTY_BITMAP_SIZE * 8).any(|index| bitmap_get(&bitmap, index)) {
+        return Err(CorruptedFileError::InvalidPropertyBitmap);
+    }
+    Ok(bitmap)
+}
+
+fn bitmap_set(bitmap: &mut [u8; PROPERTY_BITMAP_SIZE], index: usize, value: bool) {
+    if value {
+        bitmap[index / 8] |= 1 << (index % 8);
+    } else {
+        bitmap[index / 8] &= !(1 << (index % 8));
+    }
+}
+
+/// -------------------- Record slots --------------------
+/// Node and edge records are packed into record pages, `RECORDS_PER_PAGE` slots of `KB1`
+/// each. Free slots are chained through a marker and the offset of the next one.
+pub const RECORDS_PER_PAGE: usize = (PAGE_SIZE - PAGE_TRAILER_SIZE) / KB1;
+pub const FREE_RECORD_MARKER: u32 = u32::from_le_bytes(*b"NXFR");
+
 /// -------------------- Node --------------------
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -461,7 +977,8 @@ pub struct Node {
     pub id: u64,
     pub schema_id: u64,
     pub property_values: [u64; MAX_PROPERTIES_COUNT],
-    pub _reserved: [u8; 48],
+    pub present: [u8; PROPERTY_BITMAP_SIZE],
+    pub _reserved: [u8; 32],
 }
 
 impl Default for Node {
@@ -470,12 +987,72 @@ impl Default for Node {
             id: 0,
             schema_id: 0,
             property_values: [0u64; MAX_PROPERTIES_COUNT],
-            _reserved: [0u8; 48],
+            present: [0u8; PROPERTY_BITMAP_SIZE],
+            _reserved: [0u8; 32],
         }
     }
 }
 const _: () = assert!(size_of::<Node>() == KB1);
 
+impl Node {
+    /// Sets the raw value of the property at `index` and marks it as present.
+    pub fn set_property(&mut self, index: usize, value: u64) {
+        self.property_values[index] = value;
+        bitmap_set(&mut self.present, index, true);
+    }
+
+    /// Clears the property at `index`, making it absent.
+    pub fn clear_property(&mut self, index: usize) {
+        self.property_values[index] = 0;
+        bitmap_set(&mut self.present, index, false);
+    }
+
+    pub fn is_property_present(&self, index: usize) -> bool {
+        bitmap_get(&self.present, index)
+    }
+
+    pub fn serialize(&self) -> [u8; KB1] {
+        let mut buf = [0u8; KB1];
+        let mut offset = 0;
+
+        write_u64_le(self.id, &mut buf[offset..offset + 8]);
+        offset += 8;
+        write_u64_le(self.schema_id, &mut buf[offset..offset + 8]);
+        offset += 8;
+        for value in &self.property_values {
+            write_u64_le(*value, &mut buf[offset..offset + 8]);
+            offset += 8;
+        }
+        write_bytes(&self.present, &mut buf[offset..offset + PROPERTY_BITMAP_SIZE]);
+        offset += PROPERTY_BITMAP_SIZE;
+        write_bytes(&self._reserved, &mut buf[offset..offset + self._reserved.len()]);
+        offset += self._reserved.len();
+
+        assert_eq!(offset, KB1, "Node serialization size mismatch");
+
+        buf
+    }
+
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
+
+        let id = reader.u64()?;
+        let schema_id = reader.u64()?;
+        let property_values = reader.u64_array()?;
+        let present = read_property_bitmap(&mut reader)?;
+        let reserved = reader.bytes()?;
+        debug_assert_eq!(reader.offset, KB1);
+
+        Ok(Self {
+            id,
+            schema_id,
+            property_values,
+            present,
+            _reserved: reserved,
+        })
+    }
+}
+
 /// -------------------- Edge --------------------
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -485,7 +1062,8 @@ pub struct Edge {
     pub source_id: u64,
     pub destination_id: u64,
     pub property_values: [u64; MAX_PROPERTIES_COUNT],
-    pub _reserved: [u8; 32],
+    pub present: [u8; PROPERTY_BITMAP_SIZE],
+    pub _reserved: [u8; 16],
 }
 
 impl Default for Edge {
@@ -496,12 +1074,232 @@ impl Default for Edge {
             source_id: 0,
             destination_id: 0,
             property_values: [0u64; MAX_PROPERTIES_COUNT],
-            _reserved: [0u8; 32],
+            present: [0u8; PROPERTY_BITMAP_SIZE],
+            _reserved: [0u8; 16],
         }
     }
 }
 const _: () = assert!(size_of::<Edge>() == KB1);
 
+impl Edge {
+    /// Sets the raw value of the property at `index` and marks it as present.
+    pub fn set_property(&mut self, index: usize, value: u64) {
+        self.property_values[index] = value;
+        bitmap_set(&mut self.present, index, true);
+    }
+
+    /// Clears the property at `index`, making it absent.
+    pub fn clear_property(&mut self, index: usize) {
+        self.property_values[index] = 0;
+        bitmap_set(&mut self.present, index, false);
+    }
+
+    pub fn is_property_present(&self, index: usize) -> bool {
+        bitmap_get(&self.present, index)
+    }
+
+    pub fn serialize(&self) -> [u8; KB1] {
+        let mut buf = [0u8; KB1];
+        let mut offset = 0;
+
+        write_u64_le(self.id, &mut buf[offset..offset + 8]);
+        offset += 8;
+        write_u64_le(self.schema_id, &mut buf[offset..offset + 8]);
+        offset += 8;
+        write_u64_le(self.source_id, &mut buf[offset..offset + 8]);
+        offset += 8;
+        write_u64_le(self.destination_id, &mut buf[offset..offset + 8]);
+        offset += 8;
+        for value in &self.property_values {
+            write_u64_le(*value, &mut buf[offset..offset + 8]);
+            offset += 8;
+        }
+        write_bytes(&self.present, &mut buf[offset..offset + PROPERTY_BITMAP_SIZE]);
+        offset += PROPERTY_BITMAP_SIZE;
+        write_bytes(&self._reserved, &mut buf[offset..offset + self._reserved.len()]);
+        offset += self._reserved.len();
+
+        assert_eq!(offset, KB1, "Edge serialization size mismatch");
+
+        buf
+    }
+
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
+
+        let id = reader.u64()?;
+        let schema_id = reader.u64()?;
+        let source_id = reader.u64()?;
+        let destination_id = reader.u64()?;
+        let property_values = reader.u64_array()?;
+        let present = read_property_bitmap(&mut reader)?;
+        let reserved = reader.bytes()?;
+        debug_assert_eq!(reader.offset, KB1);
+
+        Ok(Self {
+            id,
+            schema_id,
+            source_id,
+            destination_id,
+            property_values,
+            present,
+            _reserved: reserved,
+        })
+    }
+}
+
+/// -------------------- Id index --------------------
+/// Node and edge ids are indexed by a B+tree with one `IndexNode` per page. Leaves map ids to
+/// record offsets and offset table positions and are chained in id order; internal nodes
+/// route an id to the child covering it. The root of each tree is registered in the indices
+/// section under the section it indexes.
+pub const INDEX_NODE_CAPACITY: usize = 167;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexNodeKind {
+    Leaf = 1,
+    Internal = 2,
+}
+
+impl TryFrom<u8> for IndexNodeKind {
+    type Error = CorruptedFileError;
+
+    fn try_from(raw: u8) -> Result<Self, Self::Error> {
+        match raw {
+            1 => Ok(IndexNodeKind::Leaf),
+            2 => Ok(IndexNodeKind::Internal),
+            _ => Err(CorruptedFileError::UnknownIndexNodeKind(raw)),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct IndexNode {
+    pub kind: IndexNodeKind,
+    pub _pad0: [u8; 5],
+    pub nb_keys: u16,
+    /// Next leaf in id order, `INVALID_OFFSET` on the last leaf and on internal nodes.
+    pub next_leaf: u64,
+    /// Sorted ids.
+    pub keys: [u64; INDEX_NODE_CAPACITY],
+    /// In a leaf, the record offset of each key. In an internal node, the `nb_keys + 1`
+    /// children: child `i` covers the ids from `keys[i - 1]` up to, but excluding, `keys[i]`.
+    pub values: [u64; INDEX_NODE_CAPACITY + 1],
+    /// In a leaf, the offset table position of each key: the offset of the chunk holding its
+    /// item plus the index of the item. Unused in internal nodes.
+    pub positions: [u64; INDEX_NODE_CAPACITY],
+    pub _reserved: [u8; 64],
+}
+const _: () = assert!(size_of::<IndexNode>() == PAGE_SIZE);
+
+impl IndexNode {
+    /// A leaf holding `keys`, each pointing at the record offset in `values` and registered
+    /// at the offset table position in `positions`.
+    pub fn leaf(keys: &[u64], values: &[u64], positions: &[u64], next_leaf: u64) -> Self {
+        assert_eq!(keys.len(), values.len(), "index leaf needs one value per key");
+        assert_eq!(keys.len(), positions.len(), "index leaf needs one position per key");
+        Self::with_entries(IndexNodeKind::Leaf, keys, values, positions, next_leaf)
+    }
+
+    /// An internal node separating `children` by `keys`.
+    pub fn internal(keys: &[u64], children: &[u64]) -> Self {
+        assert_eq!(keys.len() + 1, children.len(), "internal index node needs one child more than keys");
+        Self::with_entries(IndexNodeKind::Internal, keys, children, &[], INVALID_OFFSET)
+    }
+
+    fn with_entries(kind: IndexNodeKind, keys: &[u64], values: &[u64], positions: &[u64], next_leaf: u64) -> Self {
+        let mut node = Self {
+            kind,
+            _pad0: [0u8; 5],
+            nb_keys: keys.len() as u16,
+            next_leaf,
+            keys: [0u64; INDEX_NODE_CAPACITY],
+            values: [INVALID_OFFSET; INDEX_NODE_CAPACITY + 1],
+            positions: [INVALID_OFFSET; INDEX_NODE_CAPACITY],
+            _reserved: [0u8; 64],
+        };
+        node.keys[..keys.len()].copy_from_slice(keys);
+        node.values[..values.len()].copy_from_slice(values);
+        node.positions[..positions.len()].copy_from_slice(positions);
+        node
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.kind == IndexNodeKind::Leaf
+    }
+
+    pub fn keys(&self) -> &[u64] {
+        &self.keys[..self.nb_keys as usize]
+    }
+
+    /// Record offsets of a leaf, or children of an internal node.
+    pub fn values(&self) -> &[u64] {
+        let len = self.nb_keys as usize + !self.is_leaf() as usize;
+        &self.values[..len]
+    }
+
+    /// Offset table positions of a leaf, empty for an internal node.
+    pub fn positions(&self) -> &[u64] {
+        let len = if self.is_leaf() { self.nb_keys as usize } else { 0 };
+        &self.positions[..len]
+    }
+
+    pub fn serialize(&self) -> [u8; PAGE_SIZE] {
+        let mut buf = [0u8; PAGE_SIZE];
+        let mut offset = 0;
+
+        buf[offset] = self.kind as u8;
+        offset += 1;
+        write_bytes(&self._pad0, &mut buf[offset..offset + self._pad0.len()]);
+        offset += self._pad0.len();
+        write_u16_le(self.nb_keys, &mut buf[offset..offset + 2]);
+        offset += 2;
+        write_u64_le(self.next_leaf, &mut buf[offset..offset + 8]);
+        offset += 8;
+        for value in self.keys.iter().chain(&self.values).chain(&self.positions) {
+            write_u64_le(*value, &mut buf[offset..offset + 8]);
+            offset += 8;
+        }
+        write_bytes(&self._reserved, &mut buf[offset..offset + self._reserved.len()]);
+        offset += self._reserved.len();
+
+        assert_eq!(offset, PAGE_SIZE, "IndexNode serialization size mismatch");
+
+        buf
+    }
+
+    /// Parses an index node, rejecting unknown kinds and key counts larger than a node holds.
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CorruptedFileError> {
+        let mut reader = FieldReader::new(buf);
+
+        let kind = IndexNodeKind::try_from(reader.u8()?)?;
+        let pad0 = reader.bytes()?;
+        let nb_keys = reader.u16()?;
+        if nb_keys as usize > INDEX_NODE_CAPACITY {
+            return Err(CorruptedFileError::TooManyIndexKeys(nb_keys));
+        }
+        let next_leaf = reader.u64()?;
+        let keys = reader.u64_array()?;
+        let values = reader.u64_array()?;
+        let positions = reader.u64_array()?;
+        let reserved = reader.bytes()?;
+        debug_assert_eq!(reader.offset, PAGE_SIZE);
+
+        Ok(Self {
+            kind,
+            _pad0: pad0,
+            nb_keys,
+            next_leaf,
+            keys,
+            values,
+            positions,
+            _reserved: reserved,
+        })
+    }
+}
+
 /// -------------------- NexoraFile --------------------
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -512,18 +1310,11 @@ pub struct NexoraFile {
 
 impl Default for NexoraFile {
     fn default() -> Self {
-        // Header: offset 0
-        let mut header = NexoraHeader {
-            footer_offset: INVALID_OFFSET, // footer starts after header
-            created_unix: 0,
-            magic: FILE_HEADER_MAGIC,
-            version: 0,
-            flags: 0,
-            _reserved: [0u8; 4070],
-        };
+        // Header slots: offsets 0 and PAGE_SIZE
+        let mut header = NexoraHeader::default();
 
         // Compute offsets for other sections (all defaults start sequentially)
-        let mut offset = PAGE_SIZE as u64; // immediately after header
+        let mut offset = (HEADER_SLOT_OFFSETS.len() * PAGE_SIZE) as u64; // immediately after the header slots
 
         let name_table_offset = OffsetMetadataTable {
             nb_total_items: 0,
@@ -574,8 +1365,10 @@ impl Default for NexoraFile {
         offset += PAGE_SIZE as u64;
 
         header.footer_offset = offset;
+        header.previous_footer_offset = offset + PAGE_SIZE as u64;
+        header.end_offset = offset + 2 * PAGE_SIZE as u64;
 
-        let footer = NexoraFooter {
+        let mut footer = NexoraFooter {
             name_table_offset,
             node_schema_offset,
             edge_schema_offset,
@@ -584,63 +1377,15 @@ impl Default for NexoraFile {
             indices_offset,
             nodes_offset,
             edges_offset,
-            _reserved: [0u8; 3968],
+            ..Default::default()
         };
+        for section in Section::ALL {
+            let base_chunk_offset = footer.table(section).base_chunk_offset;
+            footer.set_tail_chunk(section, base_chunk_offset);
+        }
 
         Self { header, footer }
     }
 
 }
 const _: () = assert!(size_of::<NexoraFile>() == PAGE_SIZE * 2);
-
-
-impl NexoraFile {
-    pub fn serialize(&self) -> [u8; PAGE_SIZE * 10] {
-        let mut buf: [u8; PAGE_SIZE * 10] = [0u8; PAGE_SIZE * 10];
-        let mut offset = 0;
-
-        write_u64_le(self.header.footer_offset, &mut buf[offset..offset + 8]);
-        offset += 8;
-        write_u64_le(self.header.created_unix, &mut buf[offset..offset + 8]);
-        offset += 8;
-        write_bytes(&self.header.magic, &mut buf[offset..offset + self.header.magic.len()]);
-        offset += self.header.magic.len();
-        write_u16_le(self.header.version, &mut buf[offset..offset + 2]);
-        offset += 2;
-        write_u16_le(self.header.flags, &mut buf[offset..offset + 2]);
-        offset += 2;
-        write_bytes(&self.header._reserved, &mut buf[offset..offset + self.header._reserved.len()]);
-        offset += self.header._reserved.len();
-        
-        for _ in 0..8 {
-            let chunk = OffsetTableChunk::default().serialize();
-            buf[offset..offset + PAGE_SIZE].copy_from_slice(&chunk);
-            offset += PAGE_SIZE;
-        }
-
-        macro_rules! write_offset_table {
-            ($ot:expr) => {
-                write_u64_le($ot.nb_total_items, &mut buf[offset..offset + 8]);
-                offset += 8;
-                write_u64_le($ot.base_chunk_offset, &mut buf[offset..offset + 8]);
-                offset += 8;
-            };
-        }
-
-        write_offset_table!(self.footer.name_table_offset);
-        write_offset_table!(self.footer.node_schema_offset);
-        write_offset_table!(self.footer.edge_schema_offset);
-        write_offset_table!(self.footer.schema_properties_offset);
-        write_offset_table!(self.footer.metadata_offset);
-        write_offset_table!(self.footer.indices_offset);
-        write_offset_table!(self.footer.nodes_offset);
-        write_offset_table!(self.footer.edges_offset);
-
-        write_bytes(&self.footer._reserved, &mut buf[offset..offset + self.footer._reserved.len()]);
-        offset += self.footer._reserved.len();
-
-        assert_eq!(offset, PAGE_SIZE * 10, "NexoraFile serialization size mismatch");
-
-        buf
-    }
-}
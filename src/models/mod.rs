@@ -1,2 +1,3 @@
 pub mod file_layout;
+pub mod property_value;
 pub mod schema_builder;
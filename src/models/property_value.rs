@@ -0,0 +1,89 @@
+use crate::models::file_layout::PropertyType;
+use crate::storage_engine::engine::CorruptedFileError;
+use crate::utils::encoding::float::float::{F16, F8E4M3};
+
+/// A decoded property value.
+///
+/// Scalars are packed into the `u64` property slot of their record according to the
+/// schema's `PropertyType`; `String` and `Bytes` live on the heap and the slot holds
+/// the offset of their cell. `Null` marks an absent property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Float8(F8E4M3),
+    Float16(F16),
+    Float32(f32),
+    Float64(f64),
+    Bool(bool),
+    String(String),
+    Bytes(Vec<u8>),
+    Null,
+}
+
+impl PropertyValue {
+    /// Packs a scalar value into its slot encoding for `property_type`.
+    ///
+    /// Returns `None` when the value does not match the type or is not a scalar.
+    pub fn to_raw(&self, property_type: PropertyType) -> Option<u64> {
+        match (self, property_type) {
+            (PropertyValue::Int8(v), PropertyType::Int8) => Some(*v as i64 as u64),
+            (PropertyValue::Int16(v), PropertyType::Int16) => Some(*v as i64 as u64),
+            (PropertyValue::Int32(v), PropertyType::Int32) => Some(*v as i64 as u64),
+            (PropertyValue::Int64(v), PropertyType::Int64) => Some(*v as u64),
+            (PropertyValue::Float8(v), PropertyType::Float8) => Some(v.to_bits() as u64),
+            (PropertyValue::Float16(v), PropertyType::Float16) => Some(v.to_bits() as u64),
+            (PropertyValue::Float32(v), PropertyType::Float32) => Some(v.to_bits() as u64),
+            (PropertyValue::Float64(v), PropertyType::Float64) => Some(v.to_bits()),
+            (PropertyValue::Bool(v), PropertyType::Bool) => Some(*v as u64),
+            _ => None,
+        }
+    }
+
+    /// Unpacks a scalar slot of `property_type`. Returns `None` for heap-backed types.
+    pub fn from_raw(property_type: PropertyType, raw: u64) -> Option<Self> {
+        match property_type {
+            PropertyType::Int8 => Some(PropertyValue::Int8(raw as i8)),
+            PropertyType::Int16 => Some(PropertyValue::Int16(raw as i16)),
+            PropertyType::Int32 => Some(PropertyValue::Int32(raw as i32)),
+            PropertyType::Int64 => Some(PropertyValue::Int64(raw as i64)),
+            PropertyType::Float8 => Some(PropertyValue::Float8(F8E4M3::from_bits(raw as u8))),
+            PropertyType::Float16 => Some(PropertyValue::Float16(F16::from_bits(raw as u16))),
+            PropertyType::Float32 => Some(PropertyValue::Float32(f32::from_bits(raw as u32))),
+            PropertyType::Float64 => Some(PropertyValue::Float64(f64::from_bits(raw))),
+            PropertyType::Bool => Some(PropertyValue::Bool(raw != 0)),
+            _ => None,
+        }
+    }
+
+    /// The bytes to store on the heap for a value of `property_type`, if it is heap-backed.
+    pub fn heap_bytes(&self, property_type: PropertyType) -> Option<&[u8]> {
+        match (self, property_type) {
+            (
+                PropertyValue::String(v),
+                PropertyType::String32 | PropertyType::String64 | PropertyType::String512,
+            ) => Some(v.as_bytes()),
+            (PropertyValue::Bytes(v), PropertyType::Page) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Builds the value of a heap-backed property from the bytes of its cell, `None` if
+    /// `property_type` is not heap-backed.
+    pub fn from_heap_bytes(property_type: PropertyType, bytes: Vec<u8>) -> Result<Option<Self>, CorruptedFileError> {
+        match property_type {
+            PropertyType::String32 | PropertyType::String64 | PropertyType::String512 => {
+                let value = String::from_utf8(bytes).map_err(|_| CorruptedFileError::InvalidUtf8String)?;
+                Ok(Some(PropertyValue::String(value)))
+            }
+            PropertyType::Page => Ok(Some(PropertyValue::Bytes(bytes))),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, PropertyValue::Null)
+    }
+}
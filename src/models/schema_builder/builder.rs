@@ -1,23 +1,5 @@
 use crate::models::file_layout::MAX_PROPERTIES_COUNT;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum PropertyType {
-    Int8 = 0,
-    Int16,
-    Int32,
-    Int64,
-    Float8,
-    Float16,
-    Float32,
-    Float64,
-    String32,
-    String64,
-    String512,
-    Page,
-    Bool,
-    InvalidType,
-}
-
+pub use crate::models::file_layout::PropertyType;
 
 #[derive(Debug)]
 pub struct PropertyBuilder {
@@ -34,6 +16,18 @@ impl PropertyBuilder {
             optional,
         }
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn property_type(&self) -> PropertyType {
+        self.r#type
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
 }
 
 #[derive(Debug)]
@@ -45,7 +39,7 @@ pub struct NodeSchemaBuilder {
 impl NodeSchemaBuilder {
     pub fn new(id: u64) -> Self {
         Self {
-            id: id, 
+            id,
             properties: Vec::new(),
         }
     }
@@ -69,7 +63,7 @@ pub struct EdgeSchemaBuilder {
 impl EdgeSchemaBuilder {
     pub fn new(id: u64) -> Self {
         Self {
-            id: id,
+            id,
             properties: Vec::new(),
         }
     }
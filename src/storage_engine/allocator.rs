@@ -0,0 +1,97 @@
+use crate::models::file_layout::{FREE_PAGE_MARKER, HEADER_SLOT_OFFSETS, PAGE_SIZE};
+use crate::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+use crate::utils::encoding::endian::endian::{read_u32_le, read_u64_le, write_u32_le, write_u64_le};
+
+/// Pages the file grows by when no freed or reserved page is left.
+pub const DEFAULT_EXTENSION_PAGES: u64 = 16;
+
+/// Page accounting of a database file, as reported by [`StorageEngine::page_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageStats {
+    /// Pages up to the end of the allocated space, headers and footers included.
+    pub total_pages: u64,
+    /// Pages holding headers, footers, chunks, records, names or heap cells.
+    pub used_pages: u64,
+    /// Freed pages waiting on the free page list.
+    pub free_pages: u64,
+    /// Pages the file was extended by that were never handed out.
+    pub reserved_pages: u64,
+    /// Pages the file grows by once neither of the above is left.
+    pub extension_pages: u64,
+}
+
+/// Pages are handed out from the free page list first, then from the pages reserved by the
+/// last extension, and only then by growing the file `extension_pages` at a time. Freed
+/// pages are chained through their first bytes, the head of the list living in the footer.
+///
+/// Allocator state changes like any other footer field: it is durable once the footer of
+/// the batch is logged.
+impl StorageEngine {
+    /// Reserves a page for the caller to write.
+    pub(crate) async fn allocate_page(&mut self) -> Result<u64, StorageError> {
+        let footer = &mut self.file_layout.footer;
+
+        if footer.free_page_head != 0 {
+            let offset = footer.free_page_head;
+            self.check_free_page_offset(offset)?;
+
+            let page = self.read_page(offset).await?;
+            if read_u32_le(&page, 0) != Some(FREE_PAGE_MARKER) {
+                return Err(CorruptedFileError::InvalidFreePage(offset).into());
+            }
+            let footer = &mut self.file_layout.footer;
+            footer.free_page_head = read_u64_le(&page, 8).unwrap();
+            footer.nb_free_pages = footer.nb_free_pages.saturating_sub(1);
+            return Ok(offset);
+        }
+
+        let header = &mut self.file_layout.header;
+        if footer.nb_reserved_pages > 0 {
+            let offset = header.end_offset - footer.nb_reserved_pages * PAGE_SIZE as u64;
+            footer.nb_reserved_pages -= 1;
+            return Ok(offset);
+        }
+
+        let offset = header.end_offset;
+        header.end_offset += self.extension_pages * PAGE_SIZE as u64;
+        footer.nb_reserved_pages = self.extension_pages - 1;
+        Ok(offset)
+    }
+
+    /// Puts the page at `offset` on the free page list, to be handed out again.
+    pub(crate) async fn free_page(&mut self, offset: u64) -> Result<(), StorageError> {
+        self.check_free_page_offset(offset)?;
+
+        let mut page = [0u8; PAGE_SIZE];
+        write_u32_le(FREE_PAGE_MARKER, &mut page[0..4]);
+        write_u64_le(self.file_layout.footer.free_page_head, &mut page[8..16]);
+        self.write_page(offset, &page).await?;
+
+        let footer = &mut self.file_layout.footer;
+        footer.free_page_head = offset;
+        footer.nb_free_pages += 1;
+        Ok(())
+    }
+
+    /// Current page accounting of the file.
+    pub fn page_stats(&self) -> PageStats {
+        let footer = &self.file_layout.footer;
+        let total_pages = self.file_layout.header.end_offset / PAGE_SIZE as u64;
+        PageStats {
+            total_pages,
+            used_pages: total_pages.saturating_sub(footer.nb_free_pages + footer.nb_reserved_pages),
+            free_pages: footer.nb_free_pages,
+            reserved_pages: footer.nb_reserved_pages,
+            extension_pages: self.extension_pages,
+        }
+    }
+
+    /// Header slots never go through the allocator, and nothing lies past the allocated end.
+    fn check_free_page_offset(&self, offset: u64) -> Result<(), StorageError> {
+        let first_page = (HEADER_SLOT_OFFSETS.len() * PAGE_SIZE) as u64;
+        if offset < first_page || offset >= self.file_layout.header.end_offset || !offset.is_multiple_of(PAGE_SIZE as u64) {
+            return Err(CorruptedFileError::InvalidFreePage(offset).into());
+        }
+        Ok(())
+    }
+}
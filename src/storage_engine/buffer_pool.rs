@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::MutexGuard;
+
+use crate::models::file_layout::PAGE_SIZE;
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+use crate::storage_engine::wal::PageImages;
+use crate::utils::sync::lock::lock_unpoisoned;
+
+/// Pages the buffer pool keeps cached by default, 4 MiB worth.
+pub const DEFAULT_BUFFER_POOL_PAGES: usize = 1024;
+
+/// Buffer pool activity, as reported by [`StorageEngine::buffer_pool_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferPoolStats {
+    /// Clean pages the pool keeps before evicting the least recently used one.
+    pub capacity: usize,
+    pub resident_pages: usize,
+    /// Pages written by the open batch, held until it commits.
+    pub dirty_pages: usize,
+    pub pinned_pages: usize,
+    /// Reads served from the pool.
+    pub hits: u64,
+    /// Reads that had to go to the file.
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+#[derive(Debug)]
+struct Frame {
+    page: Box<[u8; PAGE_SIZE]>,
+    dirty: bool,
+    pins: u32,
+    /// Position of the frame in the recency order.
+    last_used: u64,
+}
+
+impl Frame {
+    fn evictable(&self) -> bool {
+        !self.dirty && self.pins == 0
+    }
+}
+
+/// -------------------- Buffer pool --------------------
+/// Pages cached by offset. Reads are served from the pool when they can, and every page
+/// read from the file is kept in it. Writes land in the pool as dirty frames, which make up
+/// the pages of the open batch: committing it logs them, writes them back to the file and
+/// marks them clean, aborting it drops them.
+///
+/// Once the pool holds more than `capacity` pages, the least recently used clean frames are
+/// evicted. Dirty and pinned frames are never evicted, so the pool can outgrow its capacity
+/// while a large batch is open.
+#[derive(Debug)]
+pub(crate) struct BufferPool {
+    capacity: usize,
+    frames: HashMap<u64, Frame>,
+    /// Offsets of the frames, least recently used first.
+    recency: BTreeMap<u64, u64>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUFFER_POOL_PAGES)
+    }
+}
+
+impl BufferPool {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            frames: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The cached image of the page at `offset`, counting the lookup as a hit or a miss.
+    pub(crate) fn get(&mut self, offset: u64) -> Option<&[u8; PAGE_SIZE]> {
+        if !self.frames.contains_key(&offset) {
+            self.misses += 1;
+            return None;
+        }
+        self.hits += 1;
+        self.touch(offset);
+        self.frames.get(&offset).map(|frame| &*frame.page)
+    }
+
+    /// Caches a page just read from the file.
+    pub(crate) fn insert_clean(&mut self, offset: u64, page: &[u8; PAGE_SIZE]) {
+        self.store(offset, page, false);
+        self.evict();
+    }
+
+    /// Stores a page written by the open batch.
+    pub(crate) fn write(&mut self, offset: u64, page: &[u8; PAGE_SIZE]) {
+        self.store(offset, page, true);
+    }
+
+    /// The pages to write back when the open batch commits.
+    pub(crate) fn dirty_pages(&self) -> PageImages {
+        self.frames
+            .iter()
+            .filter(|(_, frame)| frame.dirty)
+            .map(|(&offset, frame)| (offset, frame.page.clone()))
+            .collect()
+    }
+
+    /// End of the last dirty page, which may lie past the end of the file.
+    pub(crate) fn dirty_end(&self) -> Option<u64> {
+        self.frames
+            .iter()
+            .filter(|(_, frame)| frame.dirty)
+            .map(|(&offset, _)| offset + PAGE_SIZE as u64)
+            .max()
+    }
+
+    /// Marks every frame clean once the batch is written back.
+    pub(crate) fn mark_clean(&mut self) {
+        for frame in self.frames.values_mut() {
+            frame.dirty = false;
+        }
+        self.evict();
+    }
+
+    /// Drops the pages of an aborted batch; the file still holds their committed image.
+    pub(crate) fn discard_dirty(&mut self) {
+        let recency = &mut self.recency;
+        self.frames.retain(|_, frame| {
+            if frame.dirty {
+                recency.remove(&frame.last_used);
+            }
+            !frame.dirty
+        });
+    }
+
+    /// Keeps the page at `offset` cached until it is unpinned as many times, caching `page`
+    /// unless the pool already holds an image of it.
+    pub(crate) fn pin(&mut self, offset: u64, page: &[u8; PAGE_SIZE]) {
+        if !self.frames.contains_key(&offset) {
+            self.store(offset, page, false);
+        }
+        if let Some(frame) = self.frames.get_mut(&offset) {
+            frame.pins += 1;
+        }
+    }
+
+    pub(crate) fn unpin(&mut self, offset: u64) {
+        if let Some(frame) = self.frames.get_mut(&offset) {
+            frame.pins = frame.pins.saturating_sub(1);
+        }
+        self.evict();
+    }
+
+    pub(crate) fn stats(&self) -> BufferPoolStats {
+        BufferPoolStats {
+            capacity: self.capacity,
+            resident_pages: self.frames.len(),
+            dirty_pages: self.frames.values().filter(|frame| frame.dirty).count(),
+            pinned_pages: self.frames.values().filter(|frame| frame.pins > 0).count(),
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+        }
+    }
+
+    fn store(&mut self, offset: u64, page: &[u8; PAGE_SIZE], dirty: bool) {
+        match self.frames.get_mut(&offset) {
+            Some(frame) => {
+                *frame.page = *page;
+                frame.dirty |= dirty;
+            }
+            None => {
+                let frame = Frame { page: Box::new(*page), dirty, pins: 0, last_used: 0 };
+                self.frames.insert(offset, frame);
+            }
+        }
+        self.touch(offset);
+    }
+
+    /// Moves the frame at `offset` to the most recently used end.
+    fn touch(&mut self, offset: u64) {
+        let Some(frame) = self.frames.get_mut(&offset) else {
+            return;
+        };
+        self.recency.remove(&frame.last_used);
+        self.clock += 1;
+        frame.last_used = self.clock;
+        self.recency.insert(self.clock, offset);
+    }
+
+    /// Evicts least recently used clean, unpinned frames until the pool fits its capacity.
+    fn evict(&mut self) {
+        let excess = self.frames.len().saturating_sub(self.capacity);
+        if excess == 0 {
+            return;
+        }
+
+        let victims: Vec<(u64, u64)> = self
+            .recency
+            .iter()
+            .filter(|(_, offset)| self.frames[offset].evictable())
+            .take(excess)
+            .map(|(&last_used, &offset)| (last_used, offset))
+            .collect();
+        for (last_used, offset) in victims {
+            self.recency.remove(&last_used);
+            self.frames.remove(&offset);
+            self.evictions += 1;
+        }
+    }
+}
+
+impl StorageEngine {
+    pub(crate) fn pool(&self) -> MutexGuard<'_, BufferPool> {
+        lock_unpoisoned(&self.buffer_pool)
+    }
+
+    /// Hit, miss and occupancy counters of the buffer pool.
+    pub fn buffer_pool_stats(&self) -> BufferPoolStats {
+        self.pool().stats()
+    }
+
+    /// Keeps the page at `offset` in the buffer pool until [`StorageEngine::unpin_page`]
+    /// releases it, reading it in if it is not cached yet. Pins nest.
+    pub async fn pin_page(&self, offset: u64) -> Result<(), StorageError> {
+        let page = self.read_raw_page(offset).await?;
+        self.pool().pin(offset, &page);
+        Ok(())
+    }
+
+    /// Releases a pin taken by [`StorageEngine::pin_page`].
+    pub fn unpin_page(&self, offset: u64) {
+        self.pool().unpin(offset);
+    }
+}
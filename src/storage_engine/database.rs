@@ -0,0 +1,113 @@
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard};
+
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+use crate::storage_engine::mvcc::Snapshot;
+use crate::storage_engine::transaction::Transaction;
+use crate::utils::sync::lock::lock_unpoisoned;
+
+/// A handle to a database that can be cloned and shared between tasks.
+///
+/// A single writer at a time gets the engine through [`Database::write`] or
+/// [`Database::transaction`]; the others wait their turn in order. Readers never wait on it:
+/// [`Database::read`] hands out a snapshot of the last committed state, published again each
+/// time a writer lets go of the engine. Readers and the writer then only meet page by page,
+/// through the version store the snapshot reads superseded pages from.
+#[derive(Debug, Clone)]
+pub struct Database {
+    engine: Arc<AsyncMutex<StorageEngine>>,
+    committed: Arc<Mutex<Arc<Snapshot>>>,
+}
+
+/// Exclusive access to the engine of a [`Database`]. Dropping it publishes what was
+/// committed through it to new readers.
+#[derive(Debug)]
+pub struct WriteGuard<'a> {
+    engine: AsyncMutexGuard<'a, StorageEngine>,
+    committed: &'a Mutex<Arc<Snapshot>>,
+}
+
+impl From<StorageEngine> for Database {
+    fn from(engine: StorageEngine) -> Self {
+        Self::new(engine)
+    }
+}
+
+impl Database {
+    /// Wraps an engine opened with the desired options.
+    pub fn new(engine: StorageEngine) -> Self {
+        let committed = Arc::new(engine.committed_snapshot());
+        Self {
+            engine: Arc::new(AsyncMutex::new(engine)),
+            committed: Arc::new(Mutex::new(committed)),
+        }
+    }
+
+    /// Creates a new database file, failing if the path already exists.
+    pub async fn create(file_path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Ok(Self::new(StorageEngine::create(file_path).await?))
+    }
+
+    /// Opens an existing database file for reading and writing.
+    pub async fn load(file_path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Ok(Self::new(StorageEngine::load(file_path).await?))
+    }
+
+    /// The last committed state, without waiting for the current writer.
+    ///
+    /// The snapshot stays as it is however long it is held; call again to see later commits.
+    pub fn read(&self) -> Arc<Snapshot> {
+        lock_unpoisoned(&self.committed).clone()
+    }
+
+    /// Exclusive access to the engine, once the previous writer is done. Each mutation made
+    /// through it commits on its own.
+    pub async fn write(&self) -> WriteGuard<'_> {
+        WriteGuard { engine: self.engine.lock().await, committed: &self.committed }
+    }
+
+    /// Runs `operation` in a transaction holding exclusive access to the engine, committing
+    /// it if the operation succeeds and rolling it back otherwise.
+    pub async fn transaction<T>(
+        &self,
+        operation: impl AsyncFnOnce(&mut Transaction<'_>) -> Result<T, StorageError>,
+    ) -> Result<T, StorageError> {
+        let mut engine = self.write().await;
+        let mut transaction = engine.begin().await?;
+        // Boxed so that callers' futures do not inline the whole operation.
+        match Box::pin(operation(&mut transaction)).await {
+            Ok(value) => {
+                transaction.commit().await?;
+                Ok(value)
+            }
+            Err(err) => {
+                transaction.rollback();
+                Err(err)
+            }
+        }
+    }
+}
+
+impl Deref for WriteGuard<'_> {
+    type Target = StorageEngine;
+
+    fn deref(&self) -> &StorageEngine {
+        &self.engine
+    }
+}
+
+impl DerefMut for WriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut StorageEngine {
+        &mut self.engine
+    }
+}
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        let snapshot = Arc::new(self.engine.committed_snapshot());
+        *lock_unpoisoned(self.committed) = snapshot;
+    }
+}
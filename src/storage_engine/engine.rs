@@ -1,19 +1,94 @@
-use tokio::fs::File;
-use tokio::io::{self, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::io;
 use thiserror::Error;
 
 use crate::models::file_layout::{
-    NexoraFile, NexoraFooter, NexoraHeader, PAGE_SIZE,
-    OffsetTableChunk, OffsetItem, INVALID_OFFSET,
+    NexoraFile, NexoraFooter, FILE_FORMAT_VERSION, NexoraHeader, PropertyType, PAGE_SIZE,
+    OffsetTableChunk, OffsetItem, OffsetMetadataTable, Section, INVALID_OFFSET,
+    PROPERTY_NAME_MAX_SIZE, HEADER_SLOT_OFFSETS, OFFSET_ITEMS_PER_CHUNK, stamp_page_checksum,
+    verify_page_checksum, offset_item_position, split_offset_item_position,
 };
+use crate::storage_engine::name_table::NameTable;
+use crate::storage_engine::open_options::OpenOptions;
+use crate::storage_engine::allocator::DEFAULT_EXTENSION_PAGES;
+use crate::storage_engine::mvcc::Mvcc;
+use crate::storage_engine::id_map::IdMap;
+use crate::storage_engine::buffer_pool::BufferPool;
+use crate::storage_engine::mmap::Mapping;
+use crate::storage_engine::validation::SchemaCache;
+use crate::storage_engine::wal::Wal;
+use crate::utils::fs::positional::PositionalFile;
 
 #[derive(Debug, Error)]
 pub enum CorruptedFileError {
     #[error("Invalid magic value in file header")]
     InvalidMagicValue,
 
+    #[error("Unsupported file format version {0}")]
+    UnsupportedVersion(u16),
+
     #[error("Offset value is Invalid")]
     InvalidOffsetValue,
+
+    #[error("Offset {offset} lies beyond the end of the file ({file_len} bytes)")]
+    OffsetBeyondFileLength { offset: u64, file_len: u64 },
+
+    #[error("Header found at {0} does not belong in that slot")]
+    MisplacedHeader(u64),
+
+    #[error("Page {0} on the free page list is not a free page")]
+    InvalidFreePage(u64),
+
+    #[error("Footer counts {free} free and {reserved} reserved pages, more than the file holds")]
+    InvalidPageCounts { free: u64, reserved: u64 },
+
+    #[error("Buffer ended before the structure was fully read")]
+    Truncated,
+
+    #[error("Offset table chunk claims {0} items, more than it can hold")]
+    TooManyOffsetItems(u8),
+
+    #[error("Offset table chunk at {0} links to itself")]
+    SelfReferencingChunk(u64),
+
+    #[error("Offset table chunk at {0} is not linked back to the chunk pointing at it")]
+    BrokenChunkChain(u64),
+
+    #[error("Offset table of {0:?} holds more items than its footer counts")]
+    ItemCountMismatch(Section),
+
+    #[error("Name length {0} exceeds the maximum name size")]
+    InvalidNameLength(u8),
+
+    #[error("Schema claims {0} properties, more than a record can hold")]
+    InvalidPropertyCount(u16),
+
+    #[error("Property bitmap marks slots past the last property")]
+    InvalidPropertyBitmap,
+
+    #[error("Heap cell holds a string that is not valid UTF-8")]
+    InvalidUtf8String,
+
+    #[error("Unknown property type byte {0}")]
+    UnknownPropertyType(u8),
+
+    #[error("Unknown index node kind byte {0}")]
+    UnknownIndexNodeKind(u8),
+
+    #[error("Index node claims {0} keys, more than it can hold")]
+    TooManyIndexKeys(u16),
+
+    #[error("Index node at {0} does not fit in its tree")]
+    BrokenIndex(u64),
+
+    #[error("Index entry of id {0} does not match its offset table item")]
+    StaleIndexEntry(u64),
+
+    #[error("Checksum mismatch in page at offset {offset}")]
+    ChecksumMismatch { offset: u64 },
 }
 
 #[derive(Debug, Error)]
@@ -21,141 +96,594 @@ pub enum StorageError {
     #[error("IO error occurred due to: {0}")]
     Io(#[from] io::Error),
 
-    #[error("Corrupted file format due to: {0:?}")]
+    #[error("Corrupted file format due to: {0}")]
     Corrupted(#[from] CorruptedFileError),
+
+    #[error("Storage engine was opened in read-only mode")]
+    ReadOnly,
+
+    #[error(
+        "File format {}.{} must be migrated to {}.{} before it can be opened for writing",
+        .found >> 8, .found & 0xff, .current >> 8, .current & 0xff
+    )]
+    MigrationRequired { found: u16, current: u16 },
+
+    #[error("No record with id {0} exists")]
+    NotFound(u64),
+
+    #[error("A record with id {0} already exists")]
+    AlreadyExists(u64),
+
+    #[error("Edge endpoint node {0} does not exist")]
+    DanglingEdge(u64),
+
+    #[error("Name {0:?} is longer than {PROPERTY_NAME_MAX_SIZE} bytes")]
+    NameTooLong(String),
+
+    #[error("Schema declares {0} properties, more than the maximum allowed")]
+    TooManyProperties(usize),
+
+    #[error("No schema with id {0} exists")]
+    UnknownSchema(u64),
+
+    #[error("Property {property:?} violates its schema: {reason}")]
+    SchemaViolation { property: String, reason: String },
+
+    #[error("Values of type {0:?} are not stored on the heap")]
+    NotHeapType(PropertyType),
+
+    #[error("{len} bytes do not fit in a {property_type:?} value")]
+    ValueTooLarge { property_type: PropertyType, len: usize },
+
+    #[error("Schema has no property named {0:?}")]
+    UnknownProperty(String),
+
+    #[error("Property {property:?} of type {property_type:?} cannot hold this value")]
+    TypeMismatch { property: String, property_type: PropertyType },
+
+    #[error(
+        "File format {}.{} is newer than this build, which cannot replay the write-ahead log it left",
+        .found >> 8, .found & 0xff
+    )]
+    NewerLog { found: u16 },
+
+    #[error("A committed batch could not be written back ({0}); reopen the database to recover it")]
+    RecoveryRequired(String),
+
+    #[error("Transaction was aborted by a failed operation and has been rolled back")]
+    TransactionAborted,
+
+    #[error("Section {0:?} has no id index")]
+    NotIndexed(Section),
+
+    #[error("Section {0:?} is indexed by id and only changes through its records")]
+    Indexed(Section),
 }
 
 #[derive(Debug)]
 pub struct StorageEngine {
     pub file_path: String,
     pub file_layout: NexoraFile,
-    pub file_handle: File,
+    pub file_handle: PositionalFile,
+    pub read_only: bool,
+    pub name_table: NameTable,
+    pub(crate) schema_cache: Mutex<SchemaCache>,
+    pub(crate) wal: Wal,
+    pub(crate) mvcc: Mvcc,
+    pub(crate) extension_pages: u64,
+    pub(crate) id_map: Option<IdMap>,
+    pub(crate) buffer_pool: Mutex<BufferPool>,
+    pub(crate) mapping: Option<Mapping>,
 }
 
 impl StorageEngine {
-    pub async fn new(file_path: &str) -> Result<Self, StorageError> {
-        let file_handle = File::open(file_path).await?;
-        Ok(Self {
+    pub(crate) fn new(file_path: &Path, file_handle: PositionalFile, read_only: bool) -> Self {
+        Self {
             file_layout: NexoraFile::default(),
-            file_path: file_path.to_string(),
+            file_path: file_path.to_string_lossy().into_owned(),
             file_handle,
+            read_only,
+            name_table: NameTable::default(),
+            schema_cache: Mutex::default(),
+            wal: Wal::default(),
+            mvcc: Mvcc::default(),
+            extension_pages: DEFAULT_EXTENSION_PAGES,
+            id_map: None,
+            buffer_pool: Mutex::default(),
+            mapping: None,
+        }
+    }
+
+    /// Returns a builder to configure how the database file is opened.
+    pub fn options() -> OpenOptions {
+        OpenOptions::new()
+    }
+
+    /// Creates a new database file, failing if the path already exists.
+    pub async fn create(file_path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        OpenOptions::new().create_new(true).open(file_path).await
+    }
+
+    /// Opens an existing database file for reading and writing.
+    pub async fn load(file_path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        OpenOptions::new().open(file_path).await
+    }
+
+    /// Writes the eight empty section chunks, the footer and the header of a fresh file.
+    pub(crate) async fn initialize(&mut self) -> Result<(), StorageError> {
+        self.ensure_writable()?;
+
+        let mut layout = NexoraFile::default();
+        layout.header.created_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.file_layout = layout;
+
+        self.atomically(async |engine| {
+            for table in layout.footer.tables() {
+                engine.write_page(table.base_chunk_offset, &OffsetTableChunk::default().serialize()).await?;
+            }
+            // The first root goes to slot 0 and the switch below fills the second slot, so
+            // both hold a valid root from the start.
+            engine.write_page(layout.header.footer_offset, &layout.footer.serialize()).await?;
+            engine.log_header_chunk().await?;
+            engine.log_footer_chunk().await
         })
+        .await
     }
 
-    pub async fn load(file_path: &str) -> Result<Self, StorageError> {
-        let mut engine = Self::new(file_path).await?;
+    /// Reads the header and footer from disk and validates them against the file.
+    ///
+    /// The valid header slot of the highest generation is the root. A header torn or lost
+    /// while switching roots leaves its slot invalid, so the previous root is used instead.
+    pub(crate) async fn load_layout(&mut self) -> Result<(), StorageError> {
+        let file_len = self.logical_file_len().await?;
+        let header = self.find_root(file_len, false).await?;
+        self.load_root(header, file_len).await
+    }
 
-        let mut buffer = [0u8; 6];
-        engine.file_handle.read_exact(&mut buffer).await?;
-        engine.file_handle.seek(SeekFrom::Start(0)).await?;
+    /// Format version of the root stored in the data file itself, leaving out the log and
+    /// the buffer pool, so it can be checked before the log is replayed. `None` when no
+    /// header slot is valid yet.
+    pub(crate) async fn stored_version(&self) -> Result<Option<u16>, StorageError> {
+        let file_len = self.file_handle.metadata().await?.len();
+        match self.find_root(file_len, true).await {
+            Ok(header) => Ok(Some(header.version)),
+            Err(StorageError::Corrupted(CorruptedFileError::UnsupportedVersion(version))) => {
+                Err(CorruptedFileError::UnsupportedVersion(version).into())
+            }
+            Err(StorageError::Corrupted(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
 
-        if !NexoraHeader::verify_magic(buffer) {
-            return Err(CorruptedFileError::InvalidMagicValue.into());
+    /// The header of the highest generation among the valid slots, read from the data file
+    /// alone when `from_file` is set.
+    ///
+    /// A slot holding a newer major version fails the lookup whatever the other one holds,
+    /// since only a build that cannot read the file wrote it.
+    async fn find_root(&self, file_len: u64, from_file: bool) -> Result<NexoraHeader, StorageError> {
+        let mut root: Option<NexoraHeader> = None;
+        let mut slot_error = None;
+        for slot_offset in HEADER_SLOT_OFFSETS {
+            match self.read_header_slot(slot_offset, file_len, from_file).await {
+                Ok(header) => {
+                    if root.is_none_or(|root| header.generation > root.generation) {
+                        root = Some(header);
+                    }
+                }
+                Err(StorageError::Corrupted(CorruptedFileError::UnsupportedVersion(version))) => {
+                    return Err(CorruptedFileError::UnsupportedVersion(version).into());
+                }
+                Err(StorageError::Corrupted(err)) => {
+                    slot_error.get_or_insert(err);
+                }
+                Err(err) => return Err(err),
+            }
         }
 
-        let mut raw_header = [0u8; PAGE_SIZE];
-        engine.file_handle.read_exact(&mut raw_header).await?;
-        let header = NexoraHeader::deserialize(raw_header);
-        engine.file_layout.header = header;
+        root.ok_or_else(|| slot_error.unwrap_or(CorruptedFileError::InvalidMagicValue).into())
+    }
 
-        engine.file_handle.seek(SeekFrom::Start(header.footer_offset)).await?;
-        let mut raw_footer = [0u8; PAGE_SIZE];
-        engine.file_handle.read_exact(&mut raw_footer).await?;
-        engine.file_layout.footer = NexoraFooter::deserialize(raw_footer);
+    /// Parses the header in the slot at `slot_offset`, checking it belongs there.
+    async fn read_header_slot(&self, slot_offset: u64, file_len: u64, from_file: bool) -> Result<NexoraHeader, StorageError> {
+        // Whether pages carry checksums is only known once the header has been parsed.
+        Self::check_page_bounds(slot_offset, file_len)?;
+        let raw_header = match from_file {
+            true => self.read_file_page(slot_offset).await?,
+            false => self.read_raw_page(slot_offset).await?,
+        };
+        let header = NexoraHeader::deserialize(&raw_header)?;
+        if header.has_checksums() && !verify_page_checksum(&raw_header) {
+            return Err(CorruptedFileError::ChecksumMismatch { offset: slot_offset }.into());
+        }
+        if NexoraHeader::slot_offset(header.generation) != slot_offset
+            || (slot_offset != 0 && !header.has_header_slots())
+        {
+            return Err(CorruptedFileError::MisplacedHeader(slot_offset).into());
+        }
+        Ok(header)
+    }
+
+    /// Loads the footer `header` points at and validates every section against the file.
+    async fn load_root(&mut self, header: NexoraHeader, file_len: u64) -> Result<(), StorageError> {
+        self.file_layout.header = header;
+
+        // Minor versions only add to the format, so newer ones can still be read safely.
+        if header.version > FILE_FORMAT_VERSION {
+            self.read_only = true;
+        }
+
+        Self::check_page_bounds(header.footer_offset, file_len)?;
+        let footer = NexoraFooter::deserialize(&self.read_page(header.footer_offset).await?)?;
+        Self::check_page_counts(&header, &footer)?;
+        self.file_layout.footer = footer;
 
-        engine.file_handle.seek(SeekFrom::Start(0)).await?;
-        Ok(engine)
+        // The recorded tail can be missing or stale if a build unaware of it wrote the file;
+        // the walk finds the actual one anyway.
+        for section in Section::ALL {
+            let tail = self.validate_section(footer.table(section), file_len).await?;
+            self.file_layout.footer.set_tail_chunk(section, tail);
+        }
+
+        self.name_table = self.load_name_table().await?;
+        Ok(())
     }
 
-    /// Reads an offset table chunk from the file at a given offset.
-    pub async fn read_offset_table(&mut self, offset: u64) -> Result<OffsetTableChunk, StorageError> {
-        if offset == INVALID_OFFSET {
-            return Err(CorruptedFileError::InvalidOffsetValue.into());
+    /// Walks a section's chunk chain, checking that every chunk lies inside the file and
+    /// links back to the chunk pointing at it. Returns the offset of the last chunk.
+    ///
+    /// The back links rule out cycles, so later walks over the chain always terminate.
+    async fn validate_section(&self, table: &OffsetMetadataTable, file_len: u64) -> Result<u64, StorageError> {
+        let mut previous = INVALID_OFFSET;
+        let mut offset = table.base_chunk_offset;
+
+        while offset != INVALID_OFFSET {
+            Self::check_page_bounds(offset, file_len)?;
+
+            let chunk = self.read_offset_table(offset).await?;
+            if chunk.previous_chunk != previous {
+                return Err(CorruptedFileError::BrokenChunkChain(offset).into());
+            }
+
+            previous = offset;
+            offset = chunk.next_chunk;
         }
+        Ok(previous)
+    }
 
-        let mut raw_chunk = [0u8; PAGE_SIZE];
-        self.file_handle.seek(SeekFrom::Start(offset)).await?;
-        self.file_handle.read_exact(&mut raw_chunk).await?;
-        let chunk = OffsetTableChunk::deserialize(&raw_chunk);
-        Ok(chunk)
+    /// The allocator hands out reserved pages counting back from the end offset, so their
+    /// count, and the free pages along with it, must fit between the header slots and the end.
+    fn check_page_counts(header: &NexoraHeader, footer: &NexoraFooter) -> Result<(), StorageError> {
+        let first_page = (HEADER_SLOT_OFFSETS.len() * PAGE_SIZE) as u64;
+        let allocatable = header.end_offset.saturating_sub(first_page) / PAGE_SIZE as u64;
+        match footer.nb_free_pages.checked_add(footer.nb_reserved_pages) {
+            Some(pages) if pages <= allocatable => Ok(()),
+            _ => Err(CorruptedFileError::InvalidPageCounts {
+                free: footer.nb_free_pages,
+                reserved: footer.nb_reserved_pages,
+            }
+            .into()),
+        }
     }
 
-    /// Writes an offset table chunk to disk at the given offset.
-    async fn log_offset_chunk(&mut self, chunk: &OffsetTableChunk, offset: u64) -> Result<(), StorageError> {
-        let buf = chunk.serialize();
-        self.file_handle.seek(SeekFrom::Start(offset)).await?;
-        self.file_handle.write_all(&buf).await?;
-        self.file_handle.seek(SeekFrom::Start(0)).await?;
-        self.file_handle.flush().await?;
-        
-        self.log_footer_chunk();
-        Ok(())
+    fn check_page_bounds(offset: u64, file_len: u64) -> Result<(), StorageError> {
+        if offset == INVALID_OFFSET || !offset.is_multiple_of(PAGE_SIZE as u64) {
+            return Err(CorruptedFileError::InvalidOffsetValue.into());
+        }
+        match offset.checked_add(PAGE_SIZE as u64) {
+            Some(end) if end <= file_len => Ok(()),
+            _ => Err(CorruptedFileError::OffsetBeyondFileLength { offset, file_len }.into()),
+        }
     }
 
-    /// Log footer val
-    async fn log_footer_chunk(&mut self) -> Result<(), StorageError> {
-        let buf = self.file_layout.footer.serialize();
-        self.file_handle.seek(SeekFrom::Start(self.file_layout.header.footer_offset)).await?;
-        self.file_handle.write_all(&buf).await?;
-        self.file_handle.seek(SeekFrom::Start(0)).await?;
-        self.file_handle.flush().await?;
+    pub(crate) fn ensure_writable(&self) -> Result<(), StorageError> {
+        self.ensure_written_back()?;
+        if self.read_only {
+            return Err(StorageError::ReadOnly);
+        }
         Ok(())
     }
 
-    /// Get new offset table space
-    fn get_new_offset_table_space(&mut self) -> u64 {
-        let new_chunk_path = self.file_layout.header.footer_offset;
-        self.file_layout.header.footer_offset += PAGE_SIZE as u64;
+    /// Reads a page from the file at a given offset, verifying its checksum if the file has them.
+    pub(crate) async fn read_page(&self, offset: u64) -> Result<[u8; PAGE_SIZE], StorageError> {
+        let raw_page = self.read_raw_page(offset).await?;
+        if self.file_layout.header.has_checksums() && !verify_page_checksum(&raw_page) {
+            return Err(CorruptedFileError::ChecksumMismatch { offset }.into());
+        }
+        Ok(raw_page)
+    }
+
+    /// Reads a raw page at a given offset, from the buffer pool when it is cached there.
+    pub(crate) async fn read_raw_page(&self, offset: u64) -> Result<[u8; PAGE_SIZE], StorageError> {
+        if offset == INVALID_OFFSET {
+            return Err(CorruptedFileError::InvalidOffsetValue.into());
+        }
+        if let Some(page) = self.recovered_page(offset) {
+            return Ok(*page);
+        }
+        // A mapped file needs no cache of its own.
+        if let Some(mapping) = &self.mapping {
+            return mapping.page(&self.file_handle, offset).await;
+        }
+        if let Some(page) = self.pool().get(offset) {
+            return Ok(*page);
+        }
 
-        new_chunk_path
+        let page = match self.mvcc.image(offset) {
+            Some(page) => page,
+            // A writer keeps the old image before overwriting a page, so one showing up now
+            // means the file was changed under this snapshot while it was being read.
+            None => {
+                let raw_page = self.read_file_page(offset).await?;
+                self.mvcc.image(offset).unwrap_or(raw_page)
+            }
+        };
+        self.pool().insert_clean(offset, &page);
+        Ok(page)
     }
 
-    /// Inserts a new OffsetItem into the linked list of offset table chunks.
-    pub async fn insert_offset_item(&mut self, mut offset: u64, offset_item: OffsetItem) -> Result<(), StorageError> {
+    /// Reads a page straight from the data file, bypassing the buffer pool.
+    pub(crate) async fn read_file_page(&self, offset: u64) -> Result<[u8; PAGE_SIZE], StorageError> {
+        match self.file_handle.read_exact_at(offset).await {
+            Ok(raw_page) => Ok(raw_page),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                let file_len = self.file_handle.metadata().await?.len();
+                Err(CorruptedFileError::OffsetBeyondFileLength { offset, file_len }.into())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes a page at a given offset, stamping its checksum if the file has them.
+    ///
+    /// The page is held dirty in the buffer pool and goes through the write-ahead log when
+    /// the batch commits.
+    pub(crate) async fn write_page(&mut self, offset: u64, buf: &[u8; PAGE_SIZE]) -> Result<(), StorageError> {
+        self.ensure_writable()?;
+
         if offset == INVALID_OFFSET {
             return Err(CorruptedFileError::InvalidOffsetValue.into());
         }
 
-        loop {
-            // Load current chunk
-            let mut chunk = self.read_offset_table(offset).await?;
+        let mut page = *buf;
+        if self.file_layout.header.has_checksums() {
+            stamp_page_checksum(&mut page);
+        }
+        self.stage_page(offset, page).await
+    }
 
-            // Find first empty slot
-            if (chunk.nb_items as usize) < chunk.offset_items.len() {
-                chunk.offset_items[chunk.nb_items as usize] = offset_item;
-                chunk.nb_items += 1;
+    /// Reads an offset table chunk from the file at a given offset.
+    pub async fn read_offset_table(&self, offset: u64) -> Result<OffsetTableChunk, StorageError> {
+        let raw_chunk = self.read_page(offset).await?;
+        let chunk = OffsetTableChunk::deserialize(&raw_chunk)?;
+        if chunk.next_chunk == offset || chunk.previous_chunk == offset {
+            return Err(CorruptedFileError::SelfReferencingChunk(offset).into());
+        }
+        Ok(chunk)
+    }
 
-                // Write it back
-                self.log_offset_chunk(&chunk, offset).await?;
-                return Ok(());
-            }
+    /// Writes the footer, then the header pointing at it.
+    ///
+    /// The first call of a batch switches roots: the footer moves to the page of the previous
+    /// one and the header, a generation up, to the other slot, so the committed root stays
+    /// untouched until the batch lands. Single-slot files rewrite both in place.
+    pub(crate) async fn log_footer_chunk(&mut self) -> Result<(), StorageError> {
+        let committed_generation = self.committed_layout().header.generation;
+        let header = &mut self.file_layout.header;
+        if header.has_header_slots() && header.generation == committed_generation {
+            std::mem::swap(&mut header.footer_offset, &mut header.previous_footer_offset);
+            header.generation += 1;
+        }
 
-            // If current chunk is full, go to next
-            if chunk.next_chunk == INVALID_OFFSET {
-                // Create a new chunk
-                let mut new_chunk = OffsetTableChunk::default();
-                new_chunk.previous_chunk = offset;
-                new_chunk.offset_items[0] = offset_item;
-                new_chunk.nb_items = 1;
+        let buf = self.file_layout.footer.serialize();
+        self.write_page(self.file_layout.header.footer_offset, &buf).await?;
+        self.log_header_chunk().await
+    }
 
-                // Determine file size to place new chunk at EOF
-                let new_offset = self.get_new_offset_table_space();
-                chunk.next_chunk = new_offset;
+    /// Writes the header to the slot of its generation.
+    pub(crate) async fn log_header_chunk(&mut self) -> Result<(), StorageError> {
+        let buf = self.file_layout.header.serialize();
+        self.write_page(NexoraHeader::slot_offset(self.file_layout.header.generation), &buf).await
+    }
 
-                // Write updated current chunk
-                self.log_offset_chunk(&chunk, offset).await?;
+    /// Finds the item registered under `id` in a section's offset table.
+    ///
+    /// Returns the offset of the chunk holding it, its index within the chunk and the item itself.
+    pub async fn find_offset_item(&self, section: Section, id: u64) -> Result<Option<(u64, usize, OffsetItem)>, StorageError> {
+        let mut offset = self.file_layout.footer.table(section).base_chunk_offset;
 
-                // Write new chunk
-                self.log_offset_chunk(&new_chunk, new_offset).await?;
-                return Ok(());
+        while offset != INVALID_OFFSET {
+            let chunk = self.read_offset_table(offset).await?;
+            let items = &chunk.offset_items[..chunk.nb_items as usize];
+            if let Some(index) = items.iter().position(|item| item.id == id) {
+                return Ok(Some((offset, index, items[index])));
             }
+            offset = chunk.next_chunk;
+        }
+
+        Ok(None)
+    }
 
-            // Move to next chunk
+    /// Every item of a section's offset table, in chain order.
+    pub(crate) async fn offset_items(&self, section: Section) -> Result<Vec<OffsetItem>, StorageError> {
+        let items = self.positioned_offset_items(section).await?;
+        Ok(items.into_iter().map(|(item, _)| item).collect())
+    }
+
+    /// Every item of a section's offset table with its position, in chain order.
+    pub(crate) async fn positioned_offset_items(&self, section: Section) -> Result<Vec<(OffsetItem, u64)>, StorageError> {
+        let mut items = Vec::new();
+        let mut offset = self.file_layout.footer.table(section).base_chunk_offset;
+
+        while offset != INVALID_OFFSET {
+            let chunk = self.read_offset_table(offset).await?;
+            let chunk_items = chunk.offset_items[..chunk.nb_items as usize].iter().copied();
+            items.extend(chunk_items.enumerate().map(|(index, item)| (item, offset_item_position(offset, index))));
             offset = chunk.next_chunk;
         }
+
+        Ok(items)
+    }
+
+    /// Inserts a new OffsetItem at the end of a section's offset table.
+    ///
+    /// The node and edge sections are refused, since their items go with an index entry.
+    pub async fn insert_offset_item(&mut self, section: Section, offset_item: OffsetItem) -> Result<(), StorageError> {
+        self.insert_offset_items(section, &[offset_item]).await
+    }
+
+    /// Appends `offset_items` to a section's offset table.
+    ///
+    /// Items go to the tail chunk first, then to as many new chunks as needed; every chunk
+    /// touched is written once, whatever the number of items. The node and edge sections are
+    /// refused, since their items go with an index entry.
+    pub async fn insert_offset_items(&mut self, section: Section, offset_items: &[OffsetItem]) -> Result<(), StorageError> {
+        if Self::is_indexed(section) {
+            return Err(StorageError::Indexed(section));
+        }
+        if offset_items.is_empty() {
+            return Ok(());
+        }
+        self.atomically(async |engine| engine.append_offset_items(section, offset_items).await.map(|_| ())).await
+    }
+
+    /// Appends `offset_items` and returns the position of the first, `INVALID_OFFSET` if none.
+    pub(crate) async fn append_offset_items(&mut self, section: Section, offset_items: &[OffsetItem]) -> Result<u64, StorageError> {
+        let mut offset = self.file_layout.footer.tail_chunk(section);
+        let mut chunk = self.read_offset_table(offset).await?;
+        if chunk.next_chunk != INVALID_OFFSET {
+            return Err(CorruptedFileError::BrokenChunkChain(offset).into());
+        }
+
+        let mut remaining = offset_items;
+        let mut first = None;
+        loop {
+            let start = chunk.nb_items as usize;
+            let (head, rest) = remaining.split_at(remaining.len().min(OFFSET_ITEMS_PER_CHUNK - start));
+            if first.is_none() && !head.is_empty() {
+                first = Some(offset_item_position(offset, start));
+            }
+            chunk.offset_items[start..start + head.len()].copy_from_slice(head);
+            chunk.nb_items += head.len() as u8;
+            remaining = rest;
+            if remaining.is_empty() {
+                break;
+            }
+
+            // Chain a fresh chunk once the current one is full.
+            let new_offset = self.allocate_page().await?;
+            chunk.next_chunk = new_offset;
+            self.write_page(offset, &chunk.serialize()).await?;
+
+            chunk = OffsetTableChunk { previous_chunk: offset, ..Default::default() };
+            offset = new_offset;
+        }
+        self.write_page(offset, &chunk.serialize()).await?;
+
+        let footer = &mut self.file_layout.footer;
+        footer.table_mut(section).nb_total_items += offset_items.len() as u64;
+        footer.set_tail_chunk(section, offset);
+        self.log_footer_chunk().await?;
+        Ok(first.unwrap_or(INVALID_OFFSET))
+    }
+
+    /// Removes the item registered under `id` from a section's offset table.
+    ///
+    /// The last item of the table takes the freed slot, so every chunk but the last stays
+    /// full. A chunk left empty is unlinked and its page freed, unless it is the base chunk.
+    /// Returns the removed item, if any. The node and edge sections are refused, since their
+    /// items go with an index entry.
+    pub async fn remove_offset_item(&mut self, section: Section, id: u64) -> Result<Option<OffsetItem>, StorageError> {
+        if Self::is_indexed(section) {
+            return Err(StorageError::Indexed(section));
+        }
+        self.atomically(async |engine| {
+            let Some((offset, index, _)) = engine.find_offset_item(section, id).await? else {
+                return Ok(None);
+            };
+            let (item, _) = engine.take_offset_item(section, offset_item_position(offset, index), id).await?;
+            Ok(Some(item))
+        })
+        .await
+    }
+
+    /// Removes the item at `position` of a section's offset table, which must be registered
+    /// under `id`, filling its slot with the last item of the tail chunk.
+    ///
+    /// Returns the removed item and the id of the item moved into its slot, if another was.
+    pub(crate) async fn take_offset_item(
+        &mut self,
+        section: Section,
+        position: u64,
+        id: u64,
+    ) -> Result<(OffsetItem, Option<u64>), StorageError> {
+        let (offset, index) = split_offset_item_position(position);
+        let mut chunk = self.read_offset_table(offset).await?;
+        let item = match chunk.offset_items[..chunk.nb_items as usize].get(index) {
+            Some(item) if item.id == id => *item,
+            _ => return Err(CorruptedFileError::StaleIndexEntry(id).into()),
+        };
+
+        let donor_offset = self.file_layout.footer.tail_chunk(section);
+        let mut donor = match donor_offset == offset {
+            true => chunk,
+            false => self.read_offset_table(donor_offset).await?,
+        };
+        if donor.nb_items == 0 {
+            return Err(CorruptedFileError::BrokenChunkChain(donor_offset).into());
+        }
+        let last = donor.nb_items as usize - 1;
+        let replacement = donor.offset_items[last];
+        donor.offset_items[last] = OffsetItem::default();
+        donor.nb_items -= 1;
+
+        let moved = match donor_offset == offset {
+            true if index == last => None,
+            true => {
+                donor.offset_items[index] = replacement;
+                Some(replacement.id)
+            }
+            false => {
+                chunk.offset_items[index] = replacement;
+                self.write_page(offset, &chunk.serialize()).await?;
+                Some(replacement.id)
+            }
+        };
+        let table = self.file_layout.footer.table_mut(section);
+        table.nb_total_items = table
+            .nb_total_items
+            .checked_sub(1)
+            .ok_or(CorruptedFileError::ItemCountMismatch(section))?;
+
+        if donor.nb_items == 0 && donor.previous_chunk != INVALID_OFFSET {
+            self.unlink_offset_chunk(section, donor_offset, &donor).await?;
+        } else {
+            self.write_page(donor_offset, &donor.serialize()).await?;
+        }
+        self.log_footer_chunk().await?;
+        Ok((item, moved))
+    }
+
+    /// Links the neighbours of a non-base chunk to each other and frees its page.
+    async fn unlink_offset_chunk(&mut self, section: Section, offset: u64, chunk: &OffsetTableChunk) -> Result<(), StorageError> {
+        let mut previous = self.read_offset_table(chunk.previous_chunk).await?;
+        previous.next_chunk = chunk.next_chunk;
+        self.write_page(chunk.previous_chunk, &previous.serialize()).await?;
+
+        if chunk.next_chunk != INVALID_OFFSET {
+            let mut next = self.read_offset_table(chunk.next_chunk).await?;
+            next.previous_chunk = chunk.previous_chunk;
+            self.write_page(chunk.next_chunk, &next.serialize()).await?;
+        } else {
+            self.file_layout.footer.set_tail_chunk(section, chunk.previous_chunk);
+        }
+
+        self.free_page(offset).await
     }
 
     pub async fn close(&mut self) -> io::Result<()> {
-        self.file_handle.flush().await
+        self.file_handle.sync_data().await
     }
 }
@@ -0,0 +1,267 @@
+use crate::models::file_layout::{
+    Edge, Node, PropertyType, Section, HEAP_CELL_CLASS_OFFSET, HEAP_CELL_HEADER_SIZE, HEAP_CLASS_COUNT, HEAP_FREE_CELL,
+    MAX_PROPERTIES_COUNT, PAGE_SIZE, PAGE_TRAILER_SIZE, PROPERTY_BITMAP_SIZE, bitmap_get,
+};
+use crate::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+use crate::utils::encoding::endian::endian::{read_u64_le, write_u64_le};
+
+/// Bytes of a heap page that can be carved into cells.
+const HEAP_AREA_SIZE: usize = PAGE_SIZE - PAGE_TRAILER_SIZE;
+
+/// Maximum value length of each heap class, indexed like `NexoraFooter::heap_free_lists`.
+const HEAP_CLASS_MAX_LEN: [usize; HEAP_CLASS_COUNT] =
+    [32, 64, 512, HEAP_AREA_SIZE - HEAP_CELL_HEADER_SIZE];
+
+/// Returns the heap class storing values of `property_type`, if it is variable-length.
+pub(crate) fn heap_class(property_type: PropertyType) -> Option<usize> {
+    match property_type {
+        PropertyType::String32 => Some(0),
+        PropertyType::String64 => Some(1),
+        PropertyType::String512 => Some(2),
+        PropertyType::Page => Some(3),
+        _ => None,
+    }
+}
+
+/// Size of a single cell of a heap class, header included.
+pub(crate) fn heap_cell_size(class: usize) -> usize {
+    (HEAP_CELL_HEADER_SIZE + HEAP_CLASS_MAX_LEN[class]).next_multiple_of(8)
+}
+
+/// Whether `offset` can point at the start of a cell of `class`.
+pub(crate) fn is_heap_cell_offset(class: usize, offset: u64) -> bool {
+    let slot = (offset % PAGE_SIZE as u64) as usize;
+    let cell_size = heap_cell_size(class);
+    offset >= PAGE_SIZE as u64 && slot.is_multiple_of(cell_size) && slot + cell_size <= HEAP_AREA_SIZE
+}
+
+/// Decodes the bytes of a string cell.
+fn heap_string(bytes: Vec<u8>) -> Result<String, StorageError> {
+    String::from_utf8(bytes).map_err(|_| CorruptedFileError::InvalidUtf8String.into())
+}
+
+/// Heap pages are carved into equally sized cells of a single class. Free cells of each
+/// class form a singly linked list whose head is kept in the footer.
+impl StorageEngine {
+    /// Stores a variable-length value and returns the offset to keep in its property slot.
+    pub async fn write_heap_value(&mut self, property_type: PropertyType, bytes: &[u8]) -> Result<u64, StorageError> {
+        self.ensure_writable()?;
+
+        let Some(class) = heap_class(property_type) else {
+            return Err(StorageError::NotHeapType(property_type));
+        };
+        if bytes.len() > HEAP_CLASS_MAX_LEN[class] {
+            return Err(StorageError::ValueTooLarge { property_type, len: bytes.len() });
+        }
+
+        self.atomically(async |engine| {
+            let (offset, mut page) = engine.allocate_heap_cell(class).await?;
+            let slot = (offset % PAGE_SIZE as u64) as usize;
+            let cell = &mut page[slot..slot + heap_cell_size(class)];
+            cell.fill(0);
+            cell[..4].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+            cell[HEAP_CELL_CLASS_OFFSET] = class as u8;
+            cell[HEAP_CELL_HEADER_SIZE..HEAP_CELL_HEADER_SIZE + bytes.len()].copy_from_slice(bytes);
+
+            engine.write_page(offset - slot as u64, &page).await?;
+            engine.log_footer_chunk().await?;
+            Ok(offset)
+        })
+        .await
+    }
+
+    /// Reads the value stored in the heap cell at `offset`.
+    pub async fn read_heap_value(&self, offset: u64) -> Result<Vec<u8>, StorageError> {
+        let slot = (offset % PAGE_SIZE as u64) as usize;
+        if slot + HEAP_CELL_HEADER_SIZE > HEAP_AREA_SIZE {
+            return Err(CorruptedFileError::InvalidOffsetValue.into());
+        }
+
+        let page = self.read_page(offset - slot as u64).await?;
+        let len = u32::from_le_bytes(page[slot..slot + 4].try_into().unwrap());
+        let start = slot + HEAP_CELL_HEADER_SIZE;
+        if len == HEAP_FREE_CELL || start + len as usize > HEAP_AREA_SIZE {
+            return Err(CorruptedFileError::InvalidOffsetValue.into());
+        }
+
+        Ok(page[start..start + len as usize].to_vec())
+    }
+
+    /// Returns the heap cell at `offset` to the free list of its class.
+    ///
+    /// Fails without touching the list if the cell is not an allocated cell of that class,
+    /// since freeing a cell twice would link it into the list twice.
+    pub async fn free_heap_value(&mut self, property_type: PropertyType, offset: u64) -> Result<(), StorageError> {
+        self.ensure_writable()?;
+
+        let Some(class) = heap_class(property_type) else {
+            return Err(StorageError::NotHeapType(property_type));
+        };
+
+        self.atomically(async |engine| {
+            if engine.heap_cell_violation(class, offset).await?.is_some() {
+                return Err(CorruptedFileError::InvalidOffsetValue.into());
+            }
+            let slot = (offset % PAGE_SIZE as u64) as usize;
+            let mut page = engine.read_page(offset - slot as u64).await?;
+            let cell = &mut page[slot..slot + heap_cell_size(class)];
+            cell.fill(0);
+            cell[..4].copy_from_slice(&HEAP_FREE_CELL.to_le_bytes());
+            cell[HEAP_CELL_CLASS_OFFSET] = class as u8;
+            write_u64_le(
+                engine.file_layout.footer.heap_free_lists[class],
+                &mut cell[HEAP_CELL_HEADER_SIZE..HEAP_CELL_HEADER_SIZE + 8],
+            );
+
+            engine.write_page(offset - slot as u64, &page).await?;
+            engine.file_layout.footer.heap_free_lists[class] = offset;
+            engine.log_footer_chunk().await
+        })
+        .await
+    }
+
+    /// Why `offset` does not point at an allocated cell of `class`, if it does not.
+    pub(crate) async fn heap_cell_violation(&self, class: usize, offset: u64) -> Result<Option<String>, StorageError> {
+        if !is_heap_cell_offset(class, offset) {
+            return Ok(Some(format!("offset {offset:#x} is not a heap cell")));
+        }
+        let slot = (offset % PAGE_SIZE as u64) as usize;
+        let page_offset = offset - slot as u64;
+        if page_offset >= self.file_layout.header.end_offset {
+            return Ok(Some(format!("heap cell {offset:#x} lies beyond the end of the file")));
+        }
+
+        let page = self.read_page(page_offset).await?;
+        let len = u32::from_le_bytes(page[slot..slot + 4].try_into().unwrap());
+        let reason = if len == HEAP_FREE_CELL {
+            format!("heap cell {offset:#x} is free")
+        } else if page[slot + HEAP_CELL_CLASS_OFFSET] as usize != class || len as usize > HEAP_CLASS_MAX_LEN[class] {
+            format!("heap cell {offset:#x} does not belong to heap class {class}")
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(reason))
+    }
+
+    /// Reads the variable-length property at `index` of a node, `None` when it is absent.
+    pub async fn read_node_bytes(&self, node: &Node, index: usize) -> Result<Option<Vec<u8>>, StorageError> {
+        self.read_property_bytes(Section::NodeSchema, node.schema_id, &node.property_values, &node.present, index)
+            .await
+    }
+
+    /// Reads the string property at `index` of a node, `None` when it is absent.
+    pub async fn read_node_string(&self, node: &Node, index: usize) -> Result<Option<String>, StorageError> {
+        let bytes = self.read_node_bytes(node, index).await?;
+        bytes.map(heap_string).transpose()
+    }
+
+    /// Reads the variable-length property at `index` of an edge, `None` when it is absent.
+    pub async fn read_edge_bytes(&self, edge: &Edge, index: usize) -> Result<Option<Vec<u8>>, StorageError> {
+        self.read_property_bytes(Section::EdgeSchema, edge.schema_id, &edge.property_values, &edge.present, index)
+            .await
+    }
+
+    /// Reads the string property at `index` of an edge, `None` when it is absent.
+    pub async fn read_edge_string(&self, edge: &Edge, index: usize) -> Result<Option<String>, StorageError> {
+        let bytes = self.read_edge_bytes(edge, index).await?;
+        bytes.map(heap_string).transpose()
+    }
+
+    /// Frees the heap cells of `old` that are no longer referenced by `new`.
+    ///
+    /// Passing no new values releases every heap cell of the record, as on delete.
+    pub(crate) async fn release_heap_values(
+        &mut self,
+        section: Section,
+        schema_id: u64,
+        old: (&[u64; MAX_PROPERTIES_COUNT], &[u8; PROPERTY_BITMAP_SIZE]),
+        new: Option<(&[u64; MAX_PROPERTIES_COUNT], &[u8; PROPERTY_BITMAP_SIZE])>,
+    ) -> Result<(), StorageError> {
+        let definitions = self.schema_definitions(section, schema_id).await?;
+
+        for (index, definition) in definitions.iter().enumerate() {
+            let property_type = definition.r#type;
+            if heap_class(property_type).is_none() || !bitmap_get(old.1, index) {
+                continue;
+            }
+
+            let still_referenced = new.is_some_and(|(values, present)| {
+                bitmap_get(present, index) && values[index] == old.0[index]
+            });
+            if !still_referenced {
+                self.free_heap_value(property_type, old.0[index]).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn read_property_bytes(
+        &self,
+        section: Section,
+        schema_id: u64,
+        values: &[u64; MAX_PROPERTIES_COUNT],
+        present: &[u8; PROPERTY_BITMAP_SIZE],
+        index: usize,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        let definitions = self.schema_definitions(section, schema_id).await?;
+        let Some(definition) = definitions.get(index) else {
+            return Err(StorageError::NotFound(index as u64));
+        };
+
+        let property_type = definition.r#type;
+        if heap_class(property_type).is_none() {
+            return Err(StorageError::NotHeapType(property_type));
+        }
+        if !bitmap_get(present, index) {
+            return Ok(None);
+        }
+
+        Ok(Some(self.read_heap_value(values[index]).await?))
+    }
+
+    /// Pops a cell off the free list of `class`, carving a fresh page when the list is empty.
+    ///
+    /// Returns the cell offset together with the current image of its page.
+    async fn allocate_heap_cell(&mut self, class: usize) -> Result<(u64, [u8; PAGE_SIZE]), StorageError> {
+        let head = self.file_layout.footer.heap_free_lists[class];
+        if head != 0 {
+            if !is_heap_cell_offset(class, head) {
+                return Err(CorruptedFileError::InvalidOffsetValue.into());
+            }
+
+            let slot = (head % PAGE_SIZE as u64) as usize;
+            let page = self.read_page(head - slot as u64).await?;
+            if u32::from_le_bytes(page[slot..slot + 4].try_into().unwrap()) != HEAP_FREE_CELL {
+                return Err(CorruptedFileError::InvalidOffsetValue.into());
+            }
+
+            self.file_layout.footer.heap_free_lists[class] =
+                read_u64_le(&page, slot + HEAP_CELL_HEADER_SIZE).unwrap();
+            return Ok((head, page));
+        }
+
+        let page_offset = self.allocate_page().await?;
+        let cell_size = heap_cell_size(class);
+        let cells = HEAP_AREA_SIZE / cell_size;
+
+        // The first cell is handed out, the others are chained onto the (empty) free list.
+        let mut page = [0u8; PAGE_SIZE];
+        for index in 1..cells {
+            let slot = index * cell_size;
+            let next = if index + 1 < cells {
+                page_offset + ((index + 1) * cell_size) as u64
+            } else {
+                0
+            };
+            page[slot..slot + 4].copy_from_slice(&HEAP_FREE_CELL.to_le_bytes());
+            page[slot + HEAP_CELL_CLASS_OFFSET] = class as u8;
+            write_u64_le(next, &mut page[slot + HEAP_CELL_HEADER_SIZE..slot + HEAP_CELL_HEADER_SIZE + 8]);
+        }
+        if cells > 1 {
+            self.file_layout.footer.heap_free_lists[class] = page_offset + cell_size as u64;
+        }
+
+        Ok((page_offset, page))
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use crate::models::file_layout::Section;
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+
+/// Memory held by the id map, as reported by [`StorageEngine::id_map_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdMapStats {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Approximate bytes allocated for the entries, including the spare capacity.
+    pub memory_bytes: usize,
+}
+
+/// -------------------- Id map --------------------
+/// The offset of every node and edge, read once from the offset tables when the file is
+/// opened and updated by every insert and delete after that, so finding a record costs no
+/// page read at all.
+///
+/// Changes made inside a batch are journaled until it commits, and undone if it aborts.
+#[derive(Debug, Default)]
+pub(crate) struct IdMap {
+    nodes: HashMap<u64, u64>,
+    edges: HashMap<u64, u64>,
+    /// Previous offset of every id changed by the open batches, oldest first.
+    undo: Vec<(Section, u64, Option<u64>)>,
+}
+
+/// Position of an [`IdMap`] to roll back to, taken when a batch starts.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct IdMapMark {
+    undo_len: usize,
+}
+
+impl IdMap {
+    /// Offsets of the records of `section`, if it is one the map covers.
+    pub(crate) fn entries(&self, section: Section) -> Option<&HashMap<u64, u64>> {
+        match section {
+            Section::Nodes => Some(&self.nodes),
+            Section::Edges => Some(&self.edges),
+            _ => None,
+        }
+    }
+
+    fn entries_mut(&mut self, section: Section) -> Option<&mut HashMap<u64, u64>> {
+        match section {
+            Section::Nodes => Some(&mut self.nodes),
+            Section::Edges => Some(&mut self.edges),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, section: Section, id: u64, offset: u64) {
+        if let Some(entries) = self.entries_mut(section) {
+            let previous = entries.insert(id, offset);
+            self.undo.push((section, id, previous));
+        }
+    }
+
+    pub(crate) fn remove(&mut self, section: Section, id: u64) {
+        if let Some(entries) = self.entries_mut(section) {
+            let previous = entries.remove(&id);
+            self.undo.push((section, id, previous));
+        }
+    }
+
+    pub(crate) fn mark(&self) -> IdMapMark {
+        IdMapMark { undo_len: self.undo.len() }
+    }
+
+    /// Undoes the changes made since `mark` was taken, newest first.
+    pub(crate) fn rollback(&mut self, mark: IdMapMark) {
+        while self.undo.len() > mark.undo_len {
+            let (section, id, previous) = self.undo.pop().unwrap();
+            let entries = self.entries_mut(section).unwrap();
+            match previous {
+                Some(offset) => entries.insert(id, offset),
+                None => entries.remove(&id),
+            };
+        }
+    }
+
+    /// Drops the journal once the changes it covers are committed.
+    pub(crate) fn commit(&mut self) {
+        self.undo.clear();
+    }
+
+    pub(crate) fn stats(&self) -> IdMapStats {
+        // The tables keep a control byte next to every slot.
+        let entry_size = size_of::<(u64, u64)>() + 1;
+        IdMapStats {
+            nodes: self.nodes.len(),
+            edges: self.edges.len(),
+            memory_bytes: (self.nodes.capacity() + self.edges.capacity()) * entry_size
+                + self.undo.capacity() * size_of::<(Section, u64, Option<u64>)>(),
+        }
+    }
+}
+
+impl StorageEngine {
+    /// Reads the node and edge offset tables into a fresh id map.
+    pub(crate) async fn load_id_map(&self) -> Result<IdMap, StorageError> {
+        let mut map = IdMap::default();
+        for section in [Section::Nodes, Section::Edges] {
+            let items = self.offset_items(section).await?;
+            let entries = map.entries_mut(section).unwrap();
+            entries.reserve(items.len());
+            // Lookups through the offset tables find the first of duplicated ids.
+            for item in items {
+                entries.entry(item.id).or_insert(item.offset);
+            }
+        }
+        Ok(map)
+    }
+
+    /// Size of the id map, `None` unless the engine was opened with
+    /// [`OpenOptions::id_map`](crate::storage_engine::open_options::OpenOptions::id_map).
+    pub fn id_map_stats(&self) -> Option<IdMapStats> {
+        self.id_map.as_ref().map(IdMap::stats)
+    }
+}
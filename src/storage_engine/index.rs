@@ -0,0 +1,467 @@
+use std::collections::HashSet;
+use std::ops::{Bound, Range, RangeBounds};
+
+use crate::models::file_layout::{IndexNode, OffsetItem, Section, INDEX_NODE_CAPACITY, INVALID_OFFSET};
+use crate::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+
+/// Fewest keys a node other than the root keeps before it is refilled from a sibling.
+const INDEX_NODE_MIN_KEYS: usize = INDEX_NODE_CAPACITY / 2;
+
+/// Deepest tree walked before the index is taken for corrupted, far beyond any real file.
+const MAX_INDEX_DEPTH: usize = 32;
+
+/// An index node unpacked from its page, so that its entries live on the heap while it changes.
+#[derive(Debug)]
+struct TreeNode {
+    leaf: bool,
+    keys: Vec<u64>,
+    /// Record offsets of a leaf, children of an internal node.
+    values: Vec<u64>,
+    /// Offset table positions of a leaf, empty in an internal node.
+    positions: Vec<u64>,
+    next_leaf: u64,
+}
+
+impl TreeNode {
+    fn leaf(keys: Vec<u64>, values: Vec<u64>, positions: Vec<u64>, next_leaf: u64) -> Self {
+        Self { leaf: true, keys, values, positions, next_leaf }
+    }
+
+    fn internal(keys: Vec<u64>, children: Vec<u64>) -> Self {
+        Self { leaf: false, keys, values: children, positions: Vec::new(), next_leaf: INVALID_OFFSET }
+    }
+
+    fn unpack(node: &IndexNode) -> Self {
+        Self {
+            leaf: node.is_leaf(),
+            keys: node.keys().to_vec(),
+            values: node.values().to_vec(),
+            positions: node.positions().to_vec(),
+            next_leaf: node.next_leaf,
+        }
+    }
+
+    fn pack(&self) -> IndexNode {
+        if self.leaf {
+            IndexNode::leaf(&self.keys, &self.values, &self.positions, self.next_leaf)
+        } else {
+            IndexNode::internal(&self.keys, &self.values)
+        }
+    }
+
+    /// Moves the upper half of an overfull node to a new right neighbour stored at
+    /// `right_offset`. Returns it with the separator between both halves.
+    fn split_off(&mut self, right_offset: u64) -> (TreeNode, u64) {
+        let mid = self.keys.len() / 2;
+        if self.leaf {
+            let right = TreeNode::leaf(
+                self.keys.split_off(mid),
+                self.values.split_off(mid),
+                self.positions.split_off(mid),
+                self.next_leaf,
+            );
+            self.next_leaf = right_offset;
+            let separator = right.keys[0];
+            (right, separator)
+        } else {
+            // The middle key moves up to the parent instead of staying in either half.
+            let right = TreeNode::internal(self.keys.split_off(mid + 1), self.values.split_off(mid + 1));
+            let separator = self.keys.pop().unwrap();
+            (right, separator)
+        }
+    }
+
+    /// Takes in every entry of `right`, the neighbour found after `separator`.
+    fn absorb(&mut self, right: TreeNode, separator: u64) {
+        if !self.leaf {
+            self.keys.push(separator);
+        }
+        self.keys.extend(right.keys);
+        self.values.extend(right.values);
+        self.positions.extend(right.positions);
+        self.next_leaf = right.next_leaf;
+    }
+}
+
+/// An internal node passed on the way down, and the child that was taken.
+struct IndexStep {
+    offset: u64,
+    node: TreeNode,
+    child: usize,
+}
+
+/// Splits `len` entries into as few groups of at most `max` as possible, of even sizes.
+fn even_groups(len: usize, max: usize) -> impl Iterator<Item = Range<usize>> {
+    let count = len.div_ceil(max).max(1);
+    let (size, extra) = (len / count, len % count);
+    (0..count).map(move |group| {
+        let start = group * size + group.min(extra);
+        start..start + size + (group < extra) as usize
+    })
+}
+
+/// Pages of a tree bulk-loaded with `nb_items` ids.
+pub(crate) fn index_pages_needed(nb_items: u64) -> u64 {
+    let mut level = nb_items.div_ceil(INDEX_NODE_CAPACITY as u64).max(1);
+    let mut pages = level;
+    while level > 1 {
+        level = level.div_ceil(INDEX_NODE_CAPACITY as u64 + 1);
+        pages += level;
+    }
+    pages
+}
+
+/// The node and edge sections are each indexed by a B+tree on the record id, updated along
+/// with their offset tables. The offset tables stay authoritative: a section whose tree has
+/// not been built yet is served by scanning them, the tree is built from them on the first
+/// write, and [`StorageEngine::rebuild_index`] derives it from them again at any time.
+///
+/// Leaves also record where each id sits in the offset tables, so removing an item goes
+/// straight to its chunk instead of walking the chain.
+///
+/// Like the allocator, tree updates leave the footer for the caller to log.
+impl StorageEngine {
+    /// Whether `section` is indexed by id.
+    pub(crate) fn is_indexed(section: Section) -> bool {
+        matches!(section, Section::Nodes | Section::Edges)
+    }
+
+    /// Root of the index of `section`, if it has been built.
+    async fn index_root(&self, section: Section) -> Result<Option<u64>, StorageError> {
+        let item = self.find_offset_item(Section::Indices, section as u64).await?;
+        Ok(item.map(|(_, _, item)| item.offset))
+    }
+
+    async fn set_index_root(&mut self, section: Section, root: u64) -> Result<(), StorageError> {
+        match self.find_offset_item(Section::Indices, section as u64).await? {
+            Some((chunk_offset, index, _)) => {
+                let mut chunk = self.read_offset_table(chunk_offset).await?;
+                chunk.offset_items[index].offset = root;
+                self.write_page(chunk_offset, &chunk.serialize()).await
+            }
+            None => self.insert_offset_item(Section::Indices, OffsetItem { id: section as u64, offset: root }).await,
+        }
+    }
+
+    async fn read_index_node(&self, offset: u64) -> Result<TreeNode, StorageError> {
+        let page = self.read_page(offset).await?;
+        Ok(TreeNode::unpack(&IndexNode::deserialize(&page)?))
+    }
+
+    async fn write_index_node(&mut self, offset: u64, node: &TreeNode) -> Result<(), StorageError> {
+        let page = node.pack().serialize();
+        self.write_page(offset, &page).await
+    }
+
+    /// Offset of the record registered under `id`, taken from the id map when the engine
+    /// keeps one, and otherwise looked up through the index of the section when it has one.
+    pub(crate) async fn locate_record(&self, section: Section, id: u64) -> Result<Option<u64>, StorageError> {
+        if let Some(entries) = self.id_map.as_ref().and_then(|id_map| id_map.entries(section)) {
+            return Ok(entries.get(&id).copied());
+        }
+        if Self::is_indexed(section)
+            && let Some(root) = self.index_root(section).await?
+        {
+            let (_, _, leaf) = self.descend_index(root, id).await?;
+            return Ok(leaf.keys.binary_search(&id).ok().map(|index| leaf.values[index]));
+        }
+        Ok(self.find_offset_item(section, id).await?.map(|(_, _, item)| item.offset))
+    }
+
+    /// Items of `section` whose id falls in `range`, in id order.
+    ///
+    /// Sections without an index are answered from their offset tables instead.
+    pub async fn scan_index(&self, section: Section, range: impl RangeBounds<u64>) -> Result<Vec<OffsetItem>, StorageError> {
+        let root = match Self::is_indexed(section) {
+            true => self.index_root(section).await?,
+            false => None,
+        };
+        let Some(root) = root else {
+            let mut items = self.offset_items(section).await?;
+            items.retain(|item| range.contains(&item.id));
+            items.sort_by_key(|item| item.id);
+            return Ok(items);
+        };
+
+        let start = match range.start_bound() {
+            Bound::Included(&id) => id,
+            Bound::Excluded(&id) => match id.checked_add(1) {
+                Some(id) => id,
+                None => return Ok(Vec::new()),
+            },
+            Bound::Unbounded => 0,
+        };
+
+        let (_, mut offset, mut leaf) = self.descend_index(root, start).await?;
+        let mut items = Vec::new();
+        let mut previous = None;
+        loop {
+            for (&id, &record) in leaf.keys.iter().zip(&leaf.values) {
+                // Leaves follow each other in id order, which also rules out cycles.
+                if previous.is_some_and(|previous| previous >= id) {
+                    return Err(CorruptedFileError::BrokenIndex(offset).into());
+                }
+                previous = Some(id);
+                if id < start {
+                    continue;
+                }
+                if !range.contains(&id) {
+                    return Ok(items);
+                }
+                items.push(OffsetItem { id, offset: record });
+            }
+
+            if leaf.next_leaf == INVALID_OFFSET {
+                return Ok(items);
+            }
+            offset = leaf.next_leaf;
+            leaf = self.read_index_node(offset).await?;
+            if !leaf.leaf || leaf.keys.is_empty() {
+                return Err(CorruptedFileError::BrokenIndex(offset).into());
+            }
+        }
+    }
+
+    /// Rebuilds the index of `section` from its offset tables, replacing the current tree.
+    ///
+    /// The offset tables hold every item whatever happened to the tree, so this also
+    /// recovers from a damaged index.
+    pub async fn rebuild_index(&mut self, section: Section) -> Result<(), StorageError> {
+        self.ensure_writable()?;
+        if !Self::is_indexed(section) {
+            return Err(StorageError::NotIndexed(section));
+        }
+
+        self.atomically(async |engine| {
+            if let Some(root) = engine.index_root(section).await? {
+                // The pages of a tree too damaged to walk are left behind rather than
+                // blocking the rebuild.
+                match engine.index_pages(root).await {
+                    Ok(pages) => {
+                        for page in pages {
+                            engine.free_page(page).await?;
+                        }
+                    }
+                    Err(StorageError::Corrupted(_)) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            engine.build_index(section).await?;
+            engine.log_footer_chunk().await
+        })
+        .await
+    }
+
+    /// Registers `id -> offset` in the index of `section`, along with the position of its
+    /// offset table item, building the index when the section has none yet. The offset
+    /// tables must already hold the item.
+    pub(crate) async fn index_insert(
+        &mut self,
+        section: Section,
+        id: u64,
+        record_offset: u64,
+        position: u64,
+    ) -> Result<(), StorageError> {
+        let Some(root) = self.index_root(section).await? else {
+            return self.build_index(section).await;
+        };
+
+        let (mut path, mut offset, mut node) = self.descend_index(root, id).await?;
+        match node.keys.binary_search(&id) {
+            Ok(index) => {
+                node.values[index] = record_offset;
+                node.positions[index] = position;
+            }
+            Err(index) => {
+                node.keys.insert(index, id);
+                node.values.insert(index, record_offset);
+                node.positions.insert(index, position);
+            }
+        }
+
+        // Split the leaf when it overflows, then every ancestor the new separator overflows.
+        while node.keys.len() > INDEX_NODE_CAPACITY {
+            let right_offset = self.allocate_page().await?;
+            let (right, separator) = node.split_off(right_offset);
+            self.write_index_node(right_offset, &right).await?;
+
+            let Some(step) = path.pop() else {
+                // The root split: a new root goes on top of both halves.
+                self.write_index_node(offset, &node).await?;
+                let new_root = self.allocate_page().await?;
+                let root = TreeNode::internal(vec![separator], vec![offset, right_offset]);
+                self.write_index_node(new_root, &root).await?;
+                return self.set_index_root(section, new_root).await;
+            };
+            self.write_index_node(offset, &node).await?;
+
+            (offset, node) = (step.offset, step.node);
+            node.keys.insert(step.child, separator);
+            node.values.insert(step.child + 1, right_offset);
+        }
+        self.write_index_node(offset, &node).await
+    }
+
+    /// Drops `id` from the index of `section`, building the index first when the section has
+    /// none yet. Returns the record offset and offset table position it was registered with,
+    /// `None` if it was not.
+    pub(crate) async fn index_remove(&mut self, section: Section, id: u64) -> Result<Option<(u64, u64)>, StorageError> {
+        let root = match self.index_root(section).await? {
+            Some(root) => root,
+            None => {
+                self.build_index(section).await?;
+                self.index_root(section).await?.ok_or(StorageError::NotIndexed(section))?
+            }
+        };
+
+        let (mut path, mut offset, mut node) = self.descend_index(root, id).await?;
+        let Ok(index) = node.keys.binary_search(&id) else {
+            return Ok(None);
+        };
+        node.keys.remove(index);
+        let entry = (node.values.remove(index), node.positions.remove(index));
+
+        // Refill every node left short from a sibling, merging both when they fit in one.
+        while node.keys.len() < INDEX_NODE_MIN_KEYS
+            && let Some(IndexStep { offset: parent_offset, node: mut parent, child }) = path.pop()
+        {
+            let left_child = child.saturating_sub(1);
+            let (left_offset, right_offset) = (parent.values[left_child], parent.values[left_child + 1]);
+            let (mut left, right) = if child == left_child {
+                (node, self.read_index_node(right_offset).await?)
+            } else {
+                (self.read_index_node(left_offset).await?, node)
+            };
+            if left.leaf != right.leaf {
+                return Err(CorruptedFileError::BrokenIndex(parent.values[child]).into());
+            }
+
+            left.absorb(right, parent.keys[left_child]);
+            if left.keys.len() > INDEX_NODE_CAPACITY {
+                let (right, separator) = left.split_off(right_offset);
+                self.write_index_node(right_offset, &right).await?;
+                parent.keys[left_child] = separator;
+            } else {
+                self.free_page(right_offset).await?;
+                parent.keys.remove(left_child);
+                parent.values.remove(left_child + 1);
+            }
+            self.write_index_node(left_offset, &left).await?;
+            (offset, node) = (parent_offset, parent);
+        }
+
+        // A root left with a single child hands the tree over to it.
+        if offset == root && !node.leaf && node.keys.is_empty() {
+            self.free_page(root).await?;
+            self.set_index_root(section, node.values[0]).await?;
+        } else {
+            self.write_index_node(offset, &node).await?;
+        }
+        Ok(Some(entry))
+    }
+
+    /// Records that the item of `id` moved to `position` in the offset tables of `section`.
+    /// Sections without an index have nothing to update.
+    pub(crate) async fn index_set_position(&mut self, section: Section, id: u64, position: u64) -> Result<(), StorageError> {
+        if !Self::is_indexed(section) {
+            return Ok(());
+        }
+        let Some(root) = self.index_root(section).await? else {
+            return Ok(());
+        };
+
+        let (_, offset, mut leaf) = self.descend_index(root, id).await?;
+        let Ok(index) = leaf.keys.binary_search(&id) else {
+            return Err(CorruptedFileError::StaleIndexEntry(id).into());
+        };
+        leaf.positions[index] = position;
+        self.write_index_node(offset, &leaf).await
+    }
+
+    /// Walks from `root` down to the leaf covering `id`. Returns the internal nodes passed,
+    /// then the offset of the leaf and the leaf itself.
+    async fn descend_index(&self, root: u64, id: u64) -> Result<(Vec<IndexStep>, u64, TreeNode), StorageError> {
+        let mut path = Vec::new();
+        let mut offset = root;
+        loop {
+            let node = self.read_index_node(offset).await?;
+            if node.leaf {
+                return Ok((path, offset, node));
+            }
+            if path.len() == MAX_INDEX_DEPTH {
+                return Err(CorruptedFileError::BrokenIndex(offset).into());
+            }
+
+            let child = node.keys.partition_point(|&key| key <= id);
+            let next = node.values[child];
+            path.push(IndexStep { offset, node, child });
+            offset = next;
+        }
+    }
+
+    /// Every page of the tree rooted at `root`.
+    async fn index_pages(&self, root: u64) -> Result<Vec<u64>, StorageError> {
+        let mut pages = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = vec![root];
+
+        while let Some(offset) = pending.pop() {
+            if !seen.insert(offset) {
+                return Err(CorruptedFileError::BrokenIndex(offset).into());
+            }
+            let node = self.read_index_node(offset).await?;
+            if !node.leaf {
+                pending.extend(node.values);
+            }
+            pages.push(offset);
+        }
+        Ok(pages)
+    }
+
+    /// Bulk-loads a tree from the offset tables of `section`, filling nodes evenly level by
+    /// level, and registers it as the index of the section.
+    pub(crate) async fn build_index(&mut self, section: Section) -> Result<(), StorageError> {
+        let mut items = self.positioned_offset_items(section).await?;
+        // Lookups through the offset tables find the first of duplicated ids, so it is kept.
+        items.sort_by_key(|(item, _)| item.id);
+        items.dedup_by_key(|(item, _)| item.id);
+
+        let groups: Vec<_> = even_groups(items.len(), INDEX_NODE_CAPACITY).collect();
+        let mut offsets = Vec::with_capacity(groups.len());
+        for _ in &groups {
+            offsets.push(self.allocate_page().await?);
+        }
+
+        // First id and page of every node of the level being built.
+        let mut level = Vec::with_capacity(groups.len());
+        for (index, group) in groups.into_iter().enumerate() {
+            let entries = &items[group];
+            let next_leaf = offsets.get(index + 1).copied().unwrap_or(INVALID_OFFSET);
+            let leaf = TreeNode::leaf(
+                entries.iter().map(|(item, _)| item.id).collect(),
+                entries.iter().map(|(item, _)| item.offset).collect(),
+                entries.iter().map(|&(_, position)| position).collect(),
+                next_leaf,
+            );
+            self.write_index_node(offsets[index], &leaf).await?;
+            level.push((leaf.keys.first().copied().unwrap_or(0), offsets[index]));
+        }
+
+        while level.len() > 1 {
+            let mut parents = Vec::new();
+            for group in even_groups(level.len(), INDEX_NODE_CAPACITY + 1) {
+                let nodes = &level[group];
+                let node = TreeNode::internal(
+                    nodes[1..].iter().map(|&(first, _)| first).collect(),
+                    nodes.iter().map(|&(_, offset)| offset).collect(),
+                );
+                let offset = self.allocate_page().await?;
+                self.write_index_node(offset, &node).await?;
+                parents.push((nodes[0].0, offset));
+            }
+            level = parents;
+        }
+
+        self.set_index_root(section, level[0].1).await
+    }
+}
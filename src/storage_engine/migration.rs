@@ -0,0 +1,235 @@
+use crate::models::file_layout::{
+    format_version, OffsetTableChunk, Section, FILE_FORMAT_VERSION, HEADER_FLAG_CHECKSUMS,
+    HEADER_SLOT_OFFSETS, INVALID_OFFSET, PAGE_SIZE,
+};
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+use crate::storage_engine::index::index_pages_needed;
+
+/// A step upgrading a file in place from one format version to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Migration {
+    /// 0.0 -> 0.1: stamps a checksum on every page and sets `HEADER_FLAG_CHECKSUMS`.
+    PageChecksums,
+    /// 0.1 -> 0.2: moves the chunk off page 1 to make room for the second header slot and
+    /// reserves the second footer page.
+    HeaderSlots,
+    /// 0.2 -> 0.3: builds the id index of the node and edge sections. New records are also
+    /// packed into shared pages from then on; those stored one per page sit in the first
+    /// slot of theirs, so they are left as they are.
+    IdIndex,
+}
+
+/// Pages rewritten per batch, bounding the memory a migration holds at once.
+const MIGRATION_BATCH_PAGES: u64 = 256;
+
+/// Every known migration, in the order they apply.
+const MIGRATIONS: [Migration; 3] = [Migration::PageChecksums, Migration::HeaderSlots, Migration::IdIndex];
+
+impl Migration {
+    pub fn from_version(self) -> u16 {
+        match self {
+            Migration::PageChecksums => format_version(0, 0),
+            Migration::HeaderSlots => format_version(0, 1),
+            Migration::IdIndex => format_version(0, 2),
+        }
+    }
+
+    pub fn to_version(self) -> u16 {
+        match self {
+            Migration::PageChecksums => format_version(0, 1),
+            Migration::HeaderSlots => format_version(0, 2),
+            Migration::IdIndex => format_version(0, 3),
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Migration::PageChecksums => "stamp a CRC32C checksum on every page",
+            Migration::HeaderSlots => "alternate between two header slots and footer pages",
+            Migration::IdIndex => "index node and edge ids with a B+tree",
+        }
+    }
+}
+
+/// Outcome of [`StorageEngine::migrate`].
+#[derive(Debug, Clone)]
+pub struct MigrationReport {
+    pub from_version: u16,
+    pub to_version: u16,
+    pub steps: Vec<Migration>,
+    /// Pages rewritten, or that would be rewritten on a dry run.
+    pub pages_rewritten: u64,
+    pub dry_run: bool,
+}
+
+impl StorageEngine {
+    /// Whether the file was written by an older format version and has to be migrated
+    /// before it can be modified.
+    pub fn needs_migration(&self) -> bool {
+        self.file_layout.header.version < FILE_FORMAT_VERSION
+    }
+
+    /// Migrations that bring the file from its current version to `FILE_FORMAT_VERSION`.
+    pub fn pending_migrations(&self) -> Vec<Migration> {
+        let mut version = self.file_layout.header.version;
+        let mut steps = Vec::new();
+
+        while let Some(step) = MIGRATIONS.iter().find(|step| step.from_version() == version) {
+            steps.push(*step);
+            version = step.to_version();
+        }
+        steps
+    }
+
+    /// Upgrades the file in place to `FILE_FORMAT_VERSION`, one step at a time.
+    ///
+    /// With `dry_run` nothing is written and the report describes what would be done, which
+    /// also works on a read-only engine. Each step bumps the header version in its last
+    /// batch, so an interrupted migration leaves a file that is still valid at its previous
+    /// version.
+    pub async fn migrate(&mut self, dry_run: bool) -> Result<MigrationReport, StorageError> {
+        let mut report = MigrationReport {
+            from_version: self.file_layout.header.version,
+            to_version: self.file_layout.header.version,
+            steps: self.pending_migrations(),
+            pages_rewritten: 0,
+            dry_run,
+        };
+        if !dry_run && !report.steps.is_empty() {
+            self.ensure_writable()?;
+        }
+
+        for step in report.steps.clone() {
+            report.pages_rewritten += match step {
+                Migration::PageChecksums => self.stamp_page_checksums(dry_run).await?,
+                Migration::HeaderSlots => self.add_header_slot(dry_run).await?,
+                Migration::IdIndex => self.build_id_indexes(dry_run).await?,
+            };
+            report.to_version = step.to_version();
+
+            if !dry_run {
+                self.atomically(async |engine| {
+                    engine.file_layout.header.version = step.to_version();
+                    engine.log_footer_chunk().await
+                })
+                .await?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Rewrites every page so that `write_page` stamps its checksum.
+    async fn stamp_page_checksums(&mut self, dry_run: bool) -> Result<u64, StorageError> {
+        let nb_pages = self.file_layout.header.end_offset / PAGE_SIZE as u64;
+        if dry_run {
+            return Ok(nb_pages);
+        }
+
+        // The header and footer are written once the step completes.
+        self.file_layout.header.flags |= HEADER_FLAG_CHECKSUMS;
+        for first in (1..nb_pages - 1).step_by(MIGRATION_BATCH_PAGES as usize) {
+            let last = (first + MIGRATION_BATCH_PAGES).min(nb_pages - 1);
+            self.atomically(async |engine| {
+                for index in first..last {
+                    let offset = index * PAGE_SIZE as u64;
+                    let page = engine.read_raw_page(offset).await?;
+                    engine.write_page(offset, &page).await?;
+                }
+                Ok(())
+            })
+            .await?;
+        }
+
+        Ok(nb_pages)
+    }
+
+    /// Moves the chunk stored where the second header slot goes and reserves the second
+    /// footer page. The version changes in the same batch, since page 1 is no longer a
+    /// chunk once it does.
+    ///
+    /// The first root switch then writes the second slot; the one following the step
+    /// rewrites the first.
+    async fn add_header_slot(&mut self, dry_run: bool) -> Result<u64, StorageError> {
+        let slot_offset = HEADER_SLOT_OFFSETS[1];
+        let occupant = self.find_chunk_at(slot_offset).await?;
+        let moved_pages = occupant.as_ref().map_or(0, |(_, chunk)| {
+            1 + (chunk.previous_chunk != INVALID_OFFSET) as u64 + (chunk.next_chunk != INVALID_OFFSET) as u64
+        });
+        if dry_run {
+            return Ok(moved_pages + 2);
+        }
+
+        self.atomically(async |engine| {
+            engine.file_layout.header.version = Migration::HeaderSlots.to_version();
+            engine.file_layout.header.previous_footer_offset = engine.allocate_page().await?;
+            if let Some((section, chunk)) = occupant {
+                engine.relocate_chunk(section, slot_offset, chunk).await?;
+            }
+            engine.log_footer_chunk().await
+        })
+        .await?;
+
+        Ok(moved_pages + 2)
+    }
+
+    /// Builds the index of the node and edge sections from their offset tables.
+    async fn build_id_indexes(&mut self, dry_run: bool) -> Result<u64, StorageError> {
+        let sections = [Section::Nodes, Section::Edges];
+        let pages = sections
+            .iter()
+            .map(|&section| index_pages_needed(self.file_layout.footer.table(section).nb_total_items))
+            .sum();
+        if dry_run {
+            return Ok(pages);
+        }
+
+        self.atomically(async |engine| {
+            for section in sections {
+                engine.build_index(section).await?;
+            }
+            engine.log_footer_chunk().await
+        })
+        .await?;
+
+        Ok(pages)
+    }
+
+    /// The offset table chunk stored at `offset`, and the section it belongs to.
+    async fn find_chunk_at(&self, offset: u64) -> Result<Option<(Section, OffsetTableChunk)>, StorageError> {
+        for section in Section::ALL {
+            let mut chunk_offset = self.file_layout.footer.table(section).base_chunk_offset;
+            while chunk_offset != INVALID_OFFSET {
+                let chunk = self.read_offset_table(chunk_offset).await?;
+                if chunk_offset == offset {
+                    return Ok(Some((section, chunk)));
+                }
+                chunk_offset = chunk.next_chunk;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Copies the chunk at `from` to a new page and relinks its neighbours to it.
+    async fn relocate_chunk(&mut self, section: Section, from: u64, chunk: OffsetTableChunk) -> Result<(), StorageError> {
+        let to = self.allocate_page().await?;
+        self.write_page(to, &chunk.serialize()).await?;
+
+        if chunk.previous_chunk == INVALID_OFFSET {
+            debug_assert_eq!(self.file_layout.footer.table(section).base_chunk_offset, from);
+            self.file_layout.footer.table_mut(section).base_chunk_offset = to;
+        } else {
+            let mut previous = self.read_offset_table(chunk.previous_chunk).await?;
+            previous.next_chunk = to;
+            self.write_page(chunk.previous_chunk, &previous.serialize()).await?;
+        }
+        if chunk.next_chunk != INVALID_OFFSET {
+            let mut next = self.read_offset_table(chunk.next_chunk).await?;
+            next.previous_chunk = to;
+            self.write_page(chunk.next_chunk, &next.serialize()).await?;
+        } else {
+            self.file_layout.footer.set_tail_chunk(section, to);
+        }
+        Ok(())
+    }
+}
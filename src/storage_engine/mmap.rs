@@ -0,0 +1,124 @@
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use memmap2::Mmap;
+
+use crate::models::file_layout::PAGE_SIZE;
+use crate::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+use crate::storage_engine::open_options::OpenOptions;
+use crate::utils::fs::positional::PositionalFile;
+use crate::utils::sync::lock::lock_unpoisoned;
+
+/// -------------------- Memory-mapped reads --------------------
+/// The data file of a read-only engine, mapped in memory. Pages are copied straight out of
+/// the mapping, bypassing the buffer pool; a read only asks the file for its length.
+///
+/// The mapping covers the file as long as it was when mapped. Every read first checks the
+/// current length of the file: one that grew or shrank is mapped again, and a page the file
+/// no longer holds fails to read instead of faulting on the mapping.
+#[derive(Debug)]
+pub(crate) struct Mapping {
+    map: Mutex<Arc<Mmap>>,
+}
+
+impl Mapping {
+    pub(crate) fn new(file: &PositionalFile) -> Result<Self, StorageError> {
+        Ok(Self { map: Mutex::new(Arc::new(Self::map(file)?)) })
+    }
+
+    fn map(file: &PositionalFile) -> Result<Mmap, StorageError> {
+        // SAFETY: pages are only copied out of the mapping after checking that the file
+        // still extends past them.
+        Ok(unsafe { file.map()? })
+    }
+
+    fn current(&self) -> Arc<Mmap> {
+        lock_unpoisoned(&self.map).clone()
+    }
+
+    /// Maps the file again if its length changed, returning the new length.
+    async fn remap(&self, file: &PositionalFile) -> Result<u64, StorageError> {
+        let file_len = file.metadata().await?.len();
+        if file_len != self.current().len() as u64 {
+            let map = Arc::new(Self::map(file)?);
+            *lock_unpoisoned(&self.map) = map;
+        }
+        Ok(file_len)
+    }
+
+    /// Copies the page at `offset` out of the mapping.
+    pub(crate) async fn page(&self, file: &PositionalFile, offset: u64) -> Result<[u8; PAGE_SIZE], StorageError> {
+        let file_len = self.remap(file).await?;
+        let map = self.current();
+        let end = offset.checked_add(PAGE_SIZE as u64);
+        if end.is_none_or(|end| end > file_len.min(map.len() as u64)) {
+            return Err(CorruptedFileError::OffsetBeyondFileLength { offset, file_len }.into());
+        }
+
+        let start = offset as usize;
+        Ok(map[start..start + PAGE_SIZE].try_into().unwrap())
+    }
+}
+
+/// A read-only engine reading its file through a memory mapping.
+///
+/// The layout is loaded when it is opened and [`MmapStorageEngine::refresh`] reloads it,
+/// much like opening it again. The mapping itself is shared with the file, so pages a writer
+/// overwrites in place show through right away, before any refresh: reads racing with a
+/// writer can mix pages of both commits. Readers that need a stable view while others write
+/// take a [`Snapshot`](crate::storage_engine::mvcc::Snapshot) from the writer instead.
+///
+/// Only the reading half of the engine is reachable through it.
+#[derive(Debug)]
+pub struct MmapStorageEngine {
+    engine: StorageEngine,
+}
+
+impl MmapStorageEngine {
+    /// Maps an existing database file and loads its layout from the mapping.
+    pub async fn open(file_path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let engine = OpenOptions::new().read_only(true).mmap(true).open(file_path).await?;
+        Ok(Self { engine })
+    }
+
+    /// Maps the file again to its current length and reloads the committed layout,
+    /// including batches still waiting in the write-ahead log.
+    ///
+    /// The new state is loaded aside and only replaces the current one once complete, so an
+    /// error, such as the file having been truncated below the pages the layout needs,
+    /// leaves the engine as it was.
+    pub async fn refresh(&mut self) -> Result<(), StorageError> {
+        let current = &self.engine;
+        let mut engine = StorageEngine::new(Path::new(&current.file_path), current.file_handle.clone(), true);
+        engine.mapping = Some(Mapping::new(&engine.file_handle)?);
+        engine.recover_wal().await?;
+        engine.load_layout().await?;
+        if current.id_map.is_some() {
+            engine.id_map = Some(engine.load_id_map().await?);
+        }
+
+        self.engine = engine;
+        Ok(())
+    }
+
+    /// Length of the file the mapping currently covers.
+    pub fn mapped_len(&self) -> u64 {
+        self.engine.mapping.as_ref().map_or(0, |mapping| mapping.current().len() as u64)
+    }
+}
+
+impl Deref for MmapStorageEngine {
+    type Target = StorageEngine;
+
+    fn deref(&self) -> &StorageEngine {
+        &self.engine
+    }
+}
+
+impl StorageEngine {
+    /// Whether pages are read from a memory mapping of the file.
+    pub fn is_mapped(&self) -> bool {
+        self.mapping.is_some()
+    }
+}
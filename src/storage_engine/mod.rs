@@ -1 +1,18 @@
 pub mod engine;
+pub mod open_options;
+pub mod records;
+pub mod name_table;
+pub mod schema;
+pub mod validation;
+pub mod heap;
+pub mod properties;
+pub mod migration;
+pub mod wal;
+pub mod transaction;
+pub mod mvcc;
+pub mod allocator;
+pub mod index;
+pub mod id_map;
+pub mod buffer_pool;
+pub mod database;
+pub mod mmap;
@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::models::file_layout::{HEADER_SLOT_OFFSETS, PAGE_SIZE};
+use crate::storage_engine::buffer_pool::BufferPool;
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+use crate::utils::sync::lock::lock_unpoisoned;
+
+/// -------------------- Snapshots --------------------
+/// Committing a batch overwrites pages in place. While snapshots are open, the image a page
+/// had before each overwrite is kept in a version store shared with the writer, tagged with
+/// the lsn of the batch that replaced it. A snapshot taken at lsn `S` reads a page from the
+/// first image replaced after `S`, or from the file when the page has not changed since.
+///
+/// Images are only kept while an open snapshot can still read them, and reclaimed as soon
+/// as the last such snapshot is dropped.
+#[derive(Debug, Default)]
+pub(crate) struct VersionStore {
+    /// Lsns of the open snapshots, with how many snapshots share each one.
+    readers: BTreeMap<u64, usize>,
+    /// Superseded images of each page, by the lsn of the batch that overwrote them.
+    pages: HashMap<u64, BTreeMap<u64, Arc<[u8; PAGE_SIZE]>>>,
+}
+
+impl VersionStore {
+    /// Whether the image of the page at `offset` must be kept before a batch overwrites it.
+    fn needs_image(&self, offset: u64) -> bool {
+        let Some(&newest_reader) = self.readers.keys().next_back() else {
+            return false;
+        };
+        // Snapshots older than the last kept image already read from it.
+        let last_kept = self
+            .pages
+            .get(&offset)
+            .and_then(|versions| versions.keys().next_back().copied());
+        last_kept.is_none_or(|kept| newest_reader >= kept)
+    }
+
+    /// The image of the page at `offset` as a snapshot taken at `lsn` sees it, if it changed since.
+    fn image_at(&self, offset: u64, lsn: u64) -> Option<Arc<[u8; PAGE_SIZE]>> {
+        let versions = self.pages.get(&offset)?;
+        versions.range(lsn + 1..).next().map(|(_, image)| image.clone())
+    }
+
+    /// Drops the images no open snapshot can read anymore.
+    fn reclaim(&mut self) {
+        let readers = &self.readers;
+        self.pages.retain(|_, versions| {
+            let mut previous = 0;
+            versions.retain(|&lsn, _| {
+                let needed = readers.range(previous..lsn).next().is_some();
+                previous = lsn;
+                needed
+            });
+            !versions.is_empty()
+        });
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.pages.values().map(BTreeMap::len).sum()
+    }
+}
+
+/// The version store of an engine, and the lsn it reads at when the engine is a snapshot.
+#[derive(Debug, Default)]
+pub(crate) struct Mvcc {
+    store: Arc<Mutex<VersionStore>>,
+    snapshot: Option<u64>,
+}
+
+impl Mvcc {
+    fn store(&self) -> MutexGuard<'_, VersionStore> {
+        lock_unpoisoned(&self.store)
+    }
+
+    /// The image of the page at `offset` this snapshot reads instead of the file, if any.
+    pub(crate) fn image(&self, offset: u64) -> Option<[u8; PAGE_SIZE]> {
+        let lsn = self.snapshot?;
+        self.store().image_at(offset, lsn).map(|image| *image)
+    }
+}
+
+impl Drop for Mvcc {
+    fn drop(&mut self) {
+        let Some(lsn) = self.snapshot else {
+            return;
+        };
+        let mut store = self.store();
+        if let Some(count) = store.readers.get_mut(&lsn) {
+            *count -= 1;
+            if *count == 0 {
+                store.readers.remove(&lsn);
+            }
+        }
+        store.reclaim();
+    }
+}
+
+/// A read-only view of the database as of the last commit before it was taken.
+///
+/// It reads positionally through the file handle of the engine it came from, so it can be
+/// moved to another task and used while that engine keeps writing. Mutations fail with
+/// `StorageError::ReadOnly`.
+#[derive(Debug)]
+pub struct Snapshot {
+    engine: StorageEngine,
+}
+
+impl Snapshot {
+    /// Lsn of the last batch visible to the snapshot.
+    pub fn lsn(&self) -> u64 {
+        self.engine.wal.lsn()
+    }
+}
+
+impl Deref for Snapshot {
+    type Target = StorageEngine;
+
+    fn deref(&self) -> &StorageEngine {
+        &self.engine
+    }
+}
+
+impl DerefMut for Snapshot {
+    fn deref_mut(&mut self) -> &mut StorageEngine {
+        &mut self.engine
+    }
+}
+
+impl StorageEngine {
+    /// Takes a snapshot of the committed state of the database.
+    ///
+    /// Changes staged by an open transaction are not part of it.
+    pub async fn snapshot(&self) -> Result<Snapshot, StorageError> {
+        Ok(self.committed_snapshot())
+    }
+
+    /// [`StorageEngine::snapshot`], for callers that cannot await.
+    pub(crate) fn committed_snapshot(&self) -> Snapshot {
+        let mut engine = StorageEngine::new(Path::new(&self.file_path), self.file_handle.clone(), true);
+
+        let (layout, names) = self.committed_state();
+        engine.file_layout = layout;
+        engine.name_table = names;
+        engine.wal = self.wal.for_snapshot();
+        engine.buffer_pool = Mutex::new(BufferPool::new(self.pool().capacity()));
+
+        let lsn = self.wal.lsn();
+        *self.mvcc.store().readers.entry(lsn).or_default() += 1;
+        engine.mvcc = Mvcc { store: self.mvcc.store.clone(), snapshot: Some(lsn) };
+
+        Snapshot { engine }
+    }
+
+    /// Number of superseded page images kept for open snapshots.
+    pub fn retained_page_versions(&self) -> usize {
+        self.mvcc.store().len()
+    }
+
+    /// Keeps the current image of every page batch `lsn` is about to overwrite that an open
+    /// snapshot may still read.
+    pub(crate) async fn preserve_versions(&mut self, lsn: u64, offsets: impl IntoIterator<Item = u64>) -> Result<(), StorageError> {
+        let file_len = self.file_handle.metadata().await?.len();
+        for offset in offsets {
+            // Snapshots keep their own header, and pages past the end of the file are new.
+            if HEADER_SLOT_OFFSETS.contains(&offset) || offset >= file_len || !self.mvcc.store().needs_image(offset) {
+                continue;
+            }
+            let image = Arc::new(self.read_file_page(offset).await?);
+            self.mvcc.store().pages.entry(offset).or_default().insert(lsn, image);
+        }
+        Ok(())
+    }
+}
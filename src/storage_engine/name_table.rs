@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use crate::models::file_layout::{
+    Name, OffsetItem, Section, INVALID_OFFSET, NAMES_PER_PAGE, PAGE_SIZE, PROPERTY_NAME_MAX_SIZE,
+};
+use crate::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+
+const NAME_SIZE: u64 = size_of::<Name>() as u64;
+
+/// In-memory view of the name table section.
+///
+/// Built once when the engine loads and kept in sync by [`StorageEngine::intern`], so that
+/// lookups in either direction never touch the offset table chunks.
+#[derive(Debug, Default, Clone)]
+pub struct NameTable {
+    ids_by_name: HashMap<String, u64>,
+    names_by_id: HashMap<u64, String>,
+    last_offset: Option<u64>,
+}
+
+impl NameTable {
+    /// Returns the id of an already interned name.
+    pub fn lookup(&self, name: &str) -> Option<u64> {
+        self.ids_by_name.get(name).copied()
+    }
+
+    /// Returns the string stored under `name_id`.
+    pub fn get(&self, name_id: u64) -> Option<&str> {
+        self.names_by_id.get(&name_id).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names_by_id.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names_by_id.is_empty()
+    }
+
+    fn insert(&mut self, id: u64, name: String, offset: u64) {
+        self.ids_by_name.insert(name.clone(), id);
+        self.names_by_id.insert(id, name);
+        self.last_offset = self.last_offset.max(Some(offset));
+    }
+
+    pub(crate) fn mark(&self) -> NameTableMark {
+        NameTableMark { nb_names: self.names_by_id.len(), last_offset: self.last_offset }
+    }
+
+    /// Forgets the names interned since `mark` was taken. Ids are handed out in sequence,
+    /// so those are the ones past the marked count.
+    pub(crate) fn rollback(&mut self, mark: NameTableMark) {
+        if self.names_by_id.len() > mark.nb_names {
+            self.names_by_id.retain(|id, _| *id <= mark.nb_names as u64);
+            self.ids_by_name.retain(|_, id| *id <= mark.nb_names as u64);
+        }
+        self.last_offset = mark.last_offset;
+    }
+
+    /// Offset of the slot following the last stored name, if its page still has room.
+    fn next_slot(&self) -> Option<u64> {
+        let last = self.last_offset?;
+        let slot_index = (last % PAGE_SIZE as u64) / NAME_SIZE;
+        (slot_index + 1 < NAMES_PER_PAGE as u64).then_some(last + NAME_SIZE)
+    }
+}
+
+/// Position of a [`NameTable`] to roll back to, taken when a batch starts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NameTableMark {
+    nb_names: usize,
+    last_offset: Option<u64>,
+}
+
+/// Names are packed `NAMES_PER_PAGE` to a page and registered in the name table section.
+impl StorageEngine {
+    /// Returns the id of `name`, appending it to the name table if it is not stored yet.
+    pub async fn intern(&mut self, name: &str) -> Result<u64, StorageError> {
+        if name.len() > PROPERTY_NAME_MAX_SIZE {
+            return Err(StorageError::NameTooLong(name.to_string()));
+        }
+        if let Some(id) = self.name_table.lookup(name) {
+            return Ok(id);
+        }
+
+        self.ensure_writable()?;
+        self.atomically(async |engine| engine.append_name(name).await).await
+    }
+
+    async fn append_name(&mut self, name: &str) -> Result<u64, StorageError> {
+        // Names are never removed, so the slot after the last one is free unless its page is full.
+        let (offset, mut page) = match self.name_table.next_slot() {
+            Some(offset) => {
+                let page_offset = offset - offset % PAGE_SIZE as u64;
+                (offset, self.read_page(page_offset).await?)
+            }
+            None => (self.allocate_page().await?, [0u8; PAGE_SIZE]),
+        };
+
+        let mut value = [0u8; PROPERTY_NAME_MAX_SIZE];
+        value[..name.len()].copy_from_slice(name.as_bytes());
+        let record = Name {
+            id: self.file_layout.footer.name_table_offset.nb_total_items + 1,
+            size: name.len() as u8,
+            value,
+        };
+
+        let slot = (offset % PAGE_SIZE as u64) as usize;
+        page[slot..slot + NAME_SIZE as usize].copy_from_slice(&record.serialize());
+        self.write_page(offset - slot as u64, &page).await?;
+        self.insert_offset_item(Section::NameTable, OffsetItem { id: record.id, offset }).await?;
+
+        self.name_table.insert(record.id, name.to_string(), offset);
+        Ok(record.id)
+    }
+
+    /// Returns the string stored under `name_id`.
+    pub fn resolve(&self, name_id: u64) -> Result<String, StorageError> {
+        self.name_table
+            .get(name_id)
+            .map(str::to_string)
+            .ok_or(StorageError::NotFound(name_id))
+    }
+
+    /// Walks the name table chunk chain once and builds the in-memory lookup maps.
+    pub(crate) async fn load_name_table(&self) -> Result<NameTable, StorageError> {
+        let mut name_table = NameTable::default();
+
+        let mut cached_page: Option<(u64, [u8; PAGE_SIZE])> = None;
+        let mut chunk_offset = self.file_layout.footer.name_table_offset.base_chunk_offset;
+        while chunk_offset != INVALID_OFFSET {
+            let chunk = self.read_offset_table(chunk_offset).await?;
+            for item in &chunk.offset_items[..chunk.nb_items as usize] {
+                let slot = (item.offset % PAGE_SIZE as u64) as usize;
+                let page_offset = item.offset - slot as u64;
+
+                // Consecutive names usually share a page, so keep the last one around.
+                let page = match cached_page {
+                    Some((offset, page)) if offset == page_offset => page,
+                    _ => {
+                        let page = self.read_page(page_offset).await?;
+                        cached_page = Some((page_offset, page));
+                        page
+                    }
+                };
+
+                let Some(raw) = page.get(slot..slot + NAME_SIZE as usize) else {
+                    return Err(CorruptedFileError::InvalidOffsetValue.into());
+                };
+                let name = Name::deserialize(raw)?;
+                let value = String::from_utf8_lossy(name.as_bytes()).into_owned();
+                name_table.insert(name.id, value, item.offset);
+            }
+            chunk_offset = chunk.next_chunk;
+        }
+
+        Ok(name_table)
+    }
+}
@@ -0,0 +1,190 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use tokio::fs::OpenOptions as FileOpenOptions;
+use tokio::io;
+
+use crate::models::file_layout::FILE_FORMAT_VERSION;
+use crate::storage_engine::allocator::DEFAULT_EXTENSION_PAGES;
+use crate::storage_engine::buffer_pool::{BufferPool, DEFAULT_BUFFER_POOL_PAGES};
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+use crate::storage_engine::mmap::Mapping;
+use crate::utils::fs::positional::PositionalFile;
+
+/// Options and flags which can be used to configure how a `.nexora` file is opened.
+///
+/// Mirrors `std::fs::OpenOptions`: build it with [`OpenOptions::new`], chain the
+/// desired flags and finish with [`OpenOptions::open`]. By default an existing
+/// file is opened for reading and writing.
+#[derive(Debug, Clone)]
+pub struct OpenOptions {
+    read_only: bool,
+    create: bool,
+    create_new: bool,
+    migrate: bool,
+    extension_pages: u64,
+    id_map: bool,
+    buffer_pool_pages: usize,
+    mmap: bool,
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpenOptions {
+    pub fn new() -> Self {
+        Self {
+            read_only: false,
+            create: false,
+            create_new: false,
+            migrate: false,
+            extension_pages: DEFAULT_EXTENSION_PAGES,
+            id_map: false,
+            buffer_pool_pages: DEFAULT_BUFFER_POOL_PAGES,
+            mmap: false,
+        }
+    }
+
+    /// Opens the file without write access. Every mutation returns `StorageError::ReadOnly`.
+    pub fn read_only(&mut self, read_only: bool) -> &mut Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Opens the file with write access (the default).
+    pub fn read_write(&mut self, read_write: bool) -> &mut Self {
+        self.read_only = !read_write;
+        self
+    }
+
+    /// Creates and initializes the file if it does not exist yet.
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.create = create;
+        self
+    }
+
+    /// Creates and initializes a new file, failing if one already exists at the path.
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Upgrades files written by an older format version in place while opening them.
+    ///
+    /// Without it such files can only be opened read-only.
+    pub fn migrate(&mut self, migrate: bool) -> &mut Self {
+        self.migrate = migrate;
+        self
+    }
+
+    /// Number of pages the file grows by when the allocator runs out of free pages.
+    /// Larger increments mean fewer, bigger extensions. At least one page is always added.
+    pub fn extension_pages(&mut self, extension_pages: u64) -> &mut Self {
+        self.extension_pages = extension_pages.max(1);
+        self
+    }
+
+    /// Keeps the offset of every node and edge in memory, read from the offset tables while
+    /// opening, so looking a record up costs no page read. Opening takes longer and the map
+    /// grows with the number of records; see [`StorageEngine::id_map_stats`].
+    pub fn id_map(&mut self, id_map: bool) -> &mut Self {
+        self.id_map = id_map;
+        self
+    }
+
+    /// Number of clean pages the buffer pool caches. Zero caches nothing beyond the pages of
+    /// the open batch.
+    pub fn buffer_pool_pages(&mut self, buffer_pool_pages: usize) -> &mut Self {
+        self.buffer_pool_pages = buffer_pool_pages;
+        self
+    }
+
+    /// Reads the file through a memory mapping instead of positional reads, skipping the
+    /// buffer pool. Only read-only engines can map their file.
+    pub fn mmap(&mut self, mmap: bool) -> &mut Self {
+        self.mmap = mmap;
+        self
+    }
+
+    pub async fn open(&self, file_path: impl AsRef<Path>) -> Result<StorageEngine, StorageError> {
+        let file_path = file_path.as_ref();
+
+        if self.read_only && (self.create || self.create_new) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot create a database in read-only mode",
+            )
+            .into());
+        }
+        if self.read_only && self.migrate {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot migrate a database in read-only mode",
+            )
+            .into());
+        }
+
+        if self.mmap && !self.read_only {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot map a database opened for writing",
+            )
+            .into());
+        }
+
+        let file_handle = FileOpenOptions::new()
+            .read(true)
+            .write(!self.read_only)
+            .create(self.create)
+            .create_new(self.create_new)
+            .open(file_path)
+            .await?;
+
+        // A freshly created file is empty, anything else has to carry a valid layout.
+        let needs_init = file_handle.metadata().await?.len() == 0 && (self.create || self.create_new);
+
+        let file_handle = PositionalFile::new(file_handle.into_std().await);
+        let mut engine = StorageEngine::new(file_path, file_handle, self.read_only);
+        engine.extension_pages = self.extension_pages;
+        engine.buffer_pool = Mutex::new(BufferPool::new(self.buffer_pool_pages));
+        if self.mmap {
+            engine.mapping = Some(Mapping::new(&engine.file_handle)?);
+        }
+        // Newer minor versions stay readable, but only a build that knows them may write
+        // them, the log included.
+        if !needs_init
+            && let Some(version) = engine.stored_version().await?
+            && version > FILE_FORMAT_VERSION
+        {
+            engine.read_only = true;
+        }
+        if !engine.read_only {
+            engine.open_wal().await?;
+        }
+        if needs_init {
+            engine.reset_wal().await?;
+            engine.initialize().await?;
+        } else {
+            engine.recover_wal().await?;
+        }
+        engine.load_layout().await?;
+
+        if engine.needs_migration() && !engine.read_only {
+            if !self.migrate {
+                return Err(StorageError::MigrationRequired {
+                    found: engine.file_layout.header.version,
+                    current: FILE_FORMAT_VERSION,
+                });
+            }
+            engine.migrate(false).await?;
+        }
+        if self.id_map {
+            engine.id_map = Some(engine.load_id_map().await?);
+        }
+
+        Ok(engine)
+    }
+}
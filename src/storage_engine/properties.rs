@@ -0,0 +1,158 @@
+use crate::models::file_layout::{Edge, Node, PropertyType, Section};
+use crate::models::property_value::PropertyValue;
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+use crate::storage_engine::heap::heap_class;
+
+/// Records whose property slots are described by a schema.
+trait PropertyRecord {
+    const RECORD_SECTION: Section;
+    const SCHEMA_SECTION: Section;
+
+    fn id(&self) -> u64;
+    fn schema_id(&self) -> u64;
+    fn raw_property(&self, index: usize) -> Option<u64>;
+    fn set_raw_property(&mut self, index: usize, raw: Option<u64>);
+}
+
+impl PropertyRecord for Node {
+    const RECORD_SECTION: Section = Section::Nodes;
+    const SCHEMA_SECTION: Section = Section::NodeSchema;
+
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn schema_id(&self) -> u64 {
+        self.schema_id
+    }
+
+    fn raw_property(&self, index: usize) -> Option<u64> {
+        self.is_property_present(index).then_some(self.property_values[index])
+    }
+
+    fn set_raw_property(&mut self, index: usize, raw: Option<u64>) {
+        match raw {
+            Some(raw) => self.set_property(index, raw),
+            None => self.clear_property(index),
+        }
+    }
+}
+
+impl PropertyRecord for Edge {
+    const RECORD_SECTION: Section = Section::Edges;
+    const SCHEMA_SECTION: Section = Section::EdgeSchema;
+
+    fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn schema_id(&self) -> u64 {
+        self.schema_id
+    }
+
+    fn raw_property(&self, index: usize) -> Option<u64> {
+        self.is_property_present(index).then_some(self.property_values[index])
+    }
+
+    fn set_raw_property(&mut self, index: usize, raw: Option<u64>) {
+        match raw {
+            Some(raw) => self.set_property(index, raw),
+            None => self.clear_property(index),
+        }
+    }
+}
+
+impl Node {
+    /// Returns the value of the property called `name`, `PropertyValue::Null` when it is absent.
+    pub async fn get(&self, engine: &StorageEngine, name: &str) -> Result<PropertyValue, StorageError> {
+        engine.get_property(self, name).await
+    }
+
+    /// Sets the property called `name`. Heap-backed values are written to the heap right away
+    /// and become part of the node once it is inserted or updated.
+    pub async fn set(&mut self, engine: &mut StorageEngine, name: &str, value: PropertyValue) -> Result<(), StorageError> {
+        engine.set_property(self, name, value).await
+    }
+}
+
+impl Edge {
+    /// Returns the value of the property called `name`, `PropertyValue::Null` when it is absent.
+    pub async fn get(&self, engine: &StorageEngine, name: &str) -> Result<PropertyValue, StorageError> {
+        engine.get_property(self, name).await
+    }
+
+    /// Sets the property called `name`. Heap-backed values are written to the heap right away
+    /// and become part of the edge once it is inserted or updated.
+    pub async fn set(&mut self, engine: &mut StorageEngine, name: &str, value: PropertyValue) -> Result<(), StorageError> {
+        engine.set_property(self, name, value).await
+    }
+}
+
+impl StorageEngine {
+    /// Resolves `name` through the name table to its slot index and type in a schema.
+    async fn property_slot(&self, section: Section, schema_id: u64, name: &str) -> Result<(usize, PropertyType), StorageError> {
+        let definitions = self.schema_definitions(section, schema_id).await?;
+        let slot = self.name_table.lookup(name).and_then(|name_id| {
+            definitions
+                .iter()
+                .position(|definition| definition.name_id == name_id)
+                .map(|index| (index, definitions[index].r#type))
+        });
+
+        slot.ok_or_else(|| StorageError::UnknownProperty(name.to_string()))
+    }
+
+    async fn get_property<R: PropertyRecord>(&self, record: &R, name: &str) -> Result<PropertyValue, StorageError> {
+        let (index, property_type) = self.property_slot(R::SCHEMA_SECTION, record.schema_id(), name).await?;
+        let Some(raw) = record.raw_property(index) else {
+            return Ok(PropertyValue::Null);
+        };
+
+        let value = if heap_class(property_type).is_some() {
+            let bytes = self.read_heap_value(raw).await?;
+            PropertyValue::from_heap_bytes(property_type, bytes)?
+        } else {
+            PropertyValue::from_raw(property_type, raw)
+        };
+
+        value.ok_or_else(|| StorageError::TypeMismatch { property: name.to_string(), property_type })
+    }
+
+    async fn set_property<R: PropertyRecord>(&mut self, record: &mut R, name: &str, value: PropertyValue) -> Result<(), StorageError> {
+        self.atomically(async |engine| engine.set_record_property(record, name, value).await).await
+    }
+
+    async fn set_record_property<R: PropertyRecord>(&mut self, record: &mut R, name: &str, value: PropertyValue) -> Result<(), StorageError> {
+        let (index, property_type) = self.property_slot(R::SCHEMA_SECTION, record.schema_id(), name).await?;
+
+        let raw = if value.is_null() {
+            None
+        } else if let Some(bytes) = value.heap_bytes(property_type) {
+            Some(self.write_heap_value(property_type, bytes).await?)
+        } else {
+            match value.to_raw(property_type) {
+                Some(raw) => Some(raw),
+                None => return Err(StorageError::TypeMismatch { property: name.to_string(), property_type }),
+            }
+        };
+
+        // A heap cell written by an earlier `set` that never reached disk would leak otherwise.
+        if let Some(previous) = record.raw_property(index).filter(|_| heap_class(property_type).is_some()) {
+            let stored = self.stored_raw_property::<R>(record.id(), index).await?;
+            if stored != Some(previous) {
+                self.free_heap_value(property_type, previous).await?;
+            }
+        }
+
+        record.set_raw_property(index, raw);
+        Ok(())
+    }
+
+    /// Raw slot `index` of the persisted version of record `id`, if any.
+    async fn stored_raw_property<R: PropertyRecord>(&self, id: u64, index: usize) -> Result<Option<u64>, StorageError> {
+        Ok(match R::RECORD_SECTION {
+            Section::Edges => self.get_edge(id).await?.and_then(|edge| edge.raw_property(index)),
+            _ => self.get_node(id).await?.and_then(|node| node.raw_property(index)),
+        })
+    }
+}
@@ -0,0 +1,264 @@
+use std::ops::RangeBounds;
+
+use crate::models::file_layout::{
+    Edge, Node, OffsetItem, Section, FREE_RECORD_MARKER, HEADER_SLOT_OFFSETS, KB1, PAGE_SIZE, RECORDS_PER_PAGE,
+};
+use crate::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+use crate::utils::encoding::endian::endian::{read_u32_le, read_u64_le, write_u32_le, write_u64_le};
+
+/// Splits a record offset into the offset of its page and its position within the page,
+/// rejecting offsets that do not start a record slot.
+fn record_slot(offset: u64) -> Result<(u64, usize), StorageError> {
+    let slot = (offset % PAGE_SIZE as u64) as usize;
+    let first_page = (HEADER_SLOT_OFFSETS.len() * PAGE_SIZE) as u64;
+    if offset < first_page || !slot.is_multiple_of(KB1) || slot / KB1 >= RECORDS_PER_PAGE {
+        return Err(CorruptedFileError::InvalidOffsetValue.into());
+    }
+    Ok((offset - slot as u64, slot))
+}
+
+/// Node and edge records each occupy a `KB1` slot of a record page, found through the id
+/// index of their section. Free slots form a singly linked list whose head is kept in the
+/// footer, like heap cells.
+impl StorageEngine {
+    pub async fn insert_node(&mut self, node: &Node) -> Result<(), StorageError> {
+        self.atomically(async |engine| {
+            engine.validate_node(node).await?;
+            engine.insert_record(Section::Nodes, node.id, &node.serialize()).await
+        })
+        .await
+    }
+
+    pub async fn get_node(&self, id: u64) -> Result<Option<Node>, StorageError> {
+        let raw = self.read_record(Section::Nodes, id).await?;
+        Ok(raw.map(|raw| Node::deserialize(&raw)).transpose()?)
+    }
+
+    /// Nodes whose id falls in `range`, in id order.
+    pub async fn scan_nodes(&self, range: impl RangeBounds<u64>) -> Result<Vec<Node>, StorageError> {
+        let mut nodes = Vec::new();
+        for item in self.scan_index(Section::Nodes, range).await? {
+            nodes.push(Node::deserialize(&self.read_record_at(item.offset).await?)?);
+        }
+        Ok(nodes)
+    }
+
+    /// Rewrites a node, freeing the heap values it no longer references.
+    pub async fn update_node(&mut self, node: &Node) -> Result<(), StorageError> {
+        self.atomically(async |engine| {
+            engine.validate_node(node).await?;
+            let Some(old) = engine.get_node(node.id).await? else {
+                return Err(StorageError::NotFound(node.id));
+            };
+
+            engine.update_record(Section::Nodes, node.id, &node.serialize()).await?;
+            engine
+                .release_heap_values(
+                    Section::NodeSchema,
+                    old.schema_id,
+                    (&old.property_values, &old.present),
+                    Some((&node.property_values, &node.present)),
+                )
+                .await
+        })
+        .await
+    }
+
+    /// Deletes a node along with its heap values.
+    pub async fn delete_node(&mut self, id: u64) -> Result<(), StorageError> {
+        self.atomically(async |engine| {
+            let Some(old) = engine.get_node(id).await? else {
+                return Err(StorageError::NotFound(id));
+            };
+
+            engine.delete_record(Section::Nodes, id).await?;
+            engine
+                .release_heap_values(Section::NodeSchema, old.schema_id, (&old.property_values, &old.present), None)
+                .await
+        })
+        .await
+    }
+
+    pub async fn insert_edge(&mut self, edge: &Edge) -> Result<(), StorageError> {
+        self.atomically(async |engine| {
+            engine.validate_edge(edge).await?;
+            engine.check_edge_endpoints(edge).await?;
+            engine.insert_record(Section::Edges, edge.id, &edge.serialize()).await
+        })
+        .await
+    }
+
+    pub async fn get_edge(&self, id: u64) -> Result<Option<Edge>, StorageError> {
+        let raw = self.read_record(Section::Edges, id).await?;
+        Ok(raw.map(|raw| Edge::deserialize(&raw)).transpose()?)
+    }
+
+    /// Edges whose id falls in `range`, in id order.
+    pub async fn scan_edges(&self, range: impl RangeBounds<u64>) -> Result<Vec<Edge>, StorageError> {
+        let mut edges = Vec::new();
+        for item in self.scan_index(Section::Edges, range).await? {
+            edges.push(Edge::deserialize(&self.read_record_at(item.offset).await?)?);
+        }
+        Ok(edges)
+    }
+
+    /// Rewrites an edge, freeing the heap values it no longer references.
+    pub async fn update_edge(&mut self, edge: &Edge) -> Result<(), StorageError> {
+        self.atomically(async |engine| {
+            engine.validate_edge(edge).await?;
+            engine.check_edge_endpoints(edge).await?;
+            let Some(old) = engine.get_edge(edge.id).await? else {
+                return Err(StorageError::NotFound(edge.id));
+            };
+
+            engine.update_record(Section::Edges, edge.id, &edge.serialize()).await?;
+            engine
+                .release_heap_values(
+                    Section::EdgeSchema,
+                    old.schema_id,
+                    (&old.property_values, &old.present),
+                    Some((&edge.property_values, &edge.present)),
+                )
+                .await
+        })
+        .await
+    }
+
+    /// Deletes an edge along with its heap values.
+    pub async fn delete_edge(&mut self, id: u64) -> Result<(), StorageError> {
+        self.atomically(async |engine| {
+            let Some(old) = engine.get_edge(id).await? else {
+                return Err(StorageError::NotFound(id));
+            };
+
+            engine.delete_record(Section::Edges, id).await?;
+            engine
+                .release_heap_values(Section::EdgeSchema, old.schema_id, (&old.property_values, &old.present), None)
+                .await
+        })
+        .await
+    }
+
+    async fn check_edge_endpoints(&self, edge: &Edge) -> Result<(), StorageError> {
+        for node_id in [edge.source_id, edge.destination_id] {
+            if self.locate_record(Section::Nodes, node_id).await?.is_none() {
+                return Err(StorageError::DanglingEdge(node_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Allocates a slot for the record, writes it and registers `id -> offset` in the section.
+    async fn insert_record(&mut self, section: Section, id: u64, raw: &[u8; KB1]) -> Result<(), StorageError> {
+        self.ensure_writable()?;
+
+        if self.locate_record(section, id).await?.is_some() {
+            return Err(StorageError::AlreadyExists(id));
+        }
+
+        let (offset, mut page) = self.allocate_record_slot().await?;
+        let slot = (offset % PAGE_SIZE as u64) as usize;
+        page[slot..slot + KB1].copy_from_slice(raw);
+        self.write_page(offset - slot as u64, &page).await?;
+
+        let position = self.append_offset_items(section, &[OffsetItem { id, offset }]).await?;
+        self.index_insert(section, id, offset, position).await?;
+        if let Some(id_map) = &mut self.id_map {
+            id_map.insert(section, id, offset);
+        }
+        self.log_footer_chunk().await
+    }
+
+    async fn read_record(&self, section: Section, id: u64) -> Result<Option<[u8; KB1]>, StorageError> {
+        match self.locate_record(section, id).await? {
+            Some(offset) => Ok(Some(self.read_record_at(offset).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn read_record_at(&self, offset: u64) -> Result<[u8; KB1], StorageError> {
+        let (page_offset, slot) = record_slot(offset)?;
+        let page = self.read_page(page_offset).await?;
+        let mut raw = [0u8; KB1];
+        raw.copy_from_slice(&page[slot..slot + KB1]);
+        Ok(raw)
+    }
+
+    async fn update_record(&mut self, section: Section, id: u64, raw: &[u8; KB1]) -> Result<(), StorageError> {
+        self.ensure_writable()?;
+
+        let Some(offset) = self.locate_record(section, id).await? else {
+            return Err(StorageError::NotFound(id));
+        };
+        let (page_offset, slot) = record_slot(offset)?;
+        let mut page = self.read_page(page_offset).await?;
+        page[slot..slot + KB1].copy_from_slice(raw);
+        self.write_page(page_offset, &page).await
+    }
+
+    async fn delete_record(&mut self, section: Section, id: u64) -> Result<(), StorageError> {
+        self.ensure_writable()?;
+
+        let Some((offset, position)) = self.index_remove(section, id).await? else {
+            return Err(StorageError::NotFound(id));
+        };
+        let (_, moved) = self.take_offset_item(section, position, id).await?;
+        if let Some(moved) = moved {
+            self.index_set_position(section, moved, position).await?;
+        }
+        if let Some(id_map) = &mut self.id_map {
+            id_map.remove(section, id);
+        }
+        self.free_record_slot(offset).await?;
+        self.log_footer_chunk().await
+    }
+
+    /// Pops a slot off the free record list, carving a fresh record page when it is empty.
+    ///
+    /// Returns the slot offset together with the current image of its page.
+    async fn allocate_record_slot(&mut self) -> Result<(u64, [u8; PAGE_SIZE]), StorageError> {
+        let head = self.file_layout.footer.record_free_list;
+        if head != 0 {
+            let (page_offset, slot) = record_slot(head)?;
+            let page = self.read_page(page_offset).await?;
+            if read_u32_le(&page, slot) != Some(FREE_RECORD_MARKER) {
+                return Err(CorruptedFileError::InvalidOffsetValue.into());
+            }
+
+            self.file_layout.footer.record_free_list = read_u64_le(&page, slot + 8).unwrap();
+            return Ok((head, page));
+        }
+
+        // The first slot is handed out, the others are chained onto the (empty) free list.
+        let page_offset = self.allocate_page().await?;
+        let mut page = [0u8; PAGE_SIZE];
+        for index in 1..RECORDS_PER_PAGE {
+            let slot = index * KB1;
+            let next = match index + 1 < RECORDS_PER_PAGE {
+                true => page_offset + (slot + KB1) as u64,
+                false => 0,
+            };
+            write_u32_le(FREE_RECORD_MARKER, &mut page[slot..slot + 4]);
+            write_u64_le(next, &mut page[slot + 8..slot + 16]);
+        }
+        if RECORDS_PER_PAGE > 1 {
+            self.file_layout.footer.record_free_list = page_offset + KB1 as u64;
+        }
+
+        Ok((page_offset, page))
+    }
+
+    /// Returns the record slot at `offset` to the free record list.
+    async fn free_record_slot(&mut self, offset: u64) -> Result<(), StorageError> {
+        let (page_offset, slot) = record_slot(offset)?;
+        let mut page = self.read_page(page_offset).await?;
+        let record = &mut page[slot..slot + KB1];
+        record.fill(0);
+        write_u32_le(FREE_RECORD_MARKER, &mut record[0..4]);
+        write_u64_le(self.file_layout.footer.record_free_list, &mut record[8..16]);
+        self.write_page(page_offset, &page).await?;
+
+        self.file_layout.footer.record_free_list = offset;
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use crate::models::file_layout::{
+    EdgeSchema, NodeSchema, OffsetItem, PropertyDefinition, Section, KB1, MAX_PROPERTIES_COUNT,
+    PAGE_SIZE,
+};
+use crate::models::schema_builder::builder::{EdgeSchemaBuilder, NodeSchemaBuilder, PropertyBuilder};
+use crate::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+
+const PROPERTY_DEFINITION_SIZE: usize = size_of::<PropertyDefinition>();
+
+/// A schema page holds the `KB1` schema record followed by its property definitions.
+/// Each definition is registered in the schema properties section under its own id.
+impl StorageEngine {
+    /// Persists the schema described by `builder` and returns its id.
+    pub async fn register_node_schema(&mut self, builder: &NodeSchemaBuilder) -> Result<u64, StorageError> {
+        self.atomically(async |engine| {
+            engine.register_schema(Section::NodeSchema, builder.id, &builder.properties).await
+        })
+        .await
+    }
+
+    /// Persists the schema described by `builder` and returns its id.
+    pub async fn register_edge_schema(&mut self, builder: &EdgeSchemaBuilder) -> Result<u64, StorageError> {
+        self.atomically(async |engine| {
+            engine.register_schema(Section::EdgeSchema, builder.id, &builder.properties).await
+        })
+        .await
+    }
+
+    pub async fn get_node_schema(&self, id: u64) -> Result<Option<NodeSchema>, StorageError> {
+        let raw = self.read_schema(Section::NodeSchema, id).await?;
+        Ok(raw.map(|raw| NodeSchema::deserialize(&raw)).transpose()?)
+    }
+
+    pub async fn get_edge_schema(&self, id: u64) -> Result<Option<EdgeSchema>, StorageError> {
+        let raw = self.read_schema(Section::EdgeSchema, id).await?;
+        Ok(raw.map(|raw| EdgeSchema::deserialize(&raw)).transpose()?)
+    }
+
+    pub async fn get_property_definition(&self, id: u64) -> Result<Option<PropertyDefinition>, StorageError> {
+        let Some((_, _, item)) = self.find_offset_item(Section::SchemaProperties, id).await? else {
+            return Ok(None);
+        };
+
+        let slot = (item.offset % PAGE_SIZE as u64) as usize;
+        let page = self.read_page(item.offset - slot as u64).await?;
+        let Some(raw) = page.get(slot..slot + PROPERTY_DEFINITION_SIZE) else {
+            return Err(CorruptedFileError::InvalidOffsetValue.into());
+        };
+        Ok(Some(PropertyDefinition::deserialize(raw)?))
+    }
+
+    /// Loads a stored node schema back into its builder form.
+    pub async fn load_node_schema(&self, id: u64) -> Result<Option<NodeSchemaBuilder>, StorageError> {
+        let Some(schema) = self.get_node_schema(id).await? else {
+            return Ok(None);
+        };
+
+        let mut builder = NodeSchemaBuilder::new(schema.id);
+        builder.properties = self
+            .load_property_builders(&schema.properties[..schema.property_count as usize])
+            .await?;
+        Ok(Some(builder))
+    }
+
+    /// Loads a stored edge schema back into its builder form.
+    pub async fn load_edge_schema(&self, id: u64) -> Result<Option<EdgeSchemaBuilder>, StorageError> {
+        let Some(schema) = self.get_edge_schema(id).await? else {
+            return Ok(None);
+        };
+
+        let mut builder = EdgeSchemaBuilder::new(schema.id);
+        builder.properties = self
+            .load_property_builders(&schema.properties[..schema.property_count as usize])
+            .await?;
+        Ok(Some(builder))
+    }
+
+    async fn register_schema(&mut self, section: Section, id: u64, properties: &[PropertyBuilder]) -> Result<u64, StorageError> {
+        self.ensure_writable()?;
+
+        if properties.len() > MAX_PROPERTIES_COUNT {
+            return Err(StorageError::TooManyProperties(properties.len()));
+        }
+        if self.find_offset_item(section, id).await?.is_some() {
+            return Err(StorageError::AlreadyExists(id));
+        }
+
+        let mut name_ids = Vec::with_capacity(properties.len());
+        for property in properties {
+            name_ids.push(self.intern(property.name()).await?);
+        }
+
+        let page_offset = self.allocate_page().await?;
+        let first_property_id = self.file_layout.footer.schema_properties_offset.nb_total_items + 1;
+
+        let mut property_ids = [0u64; MAX_PROPERTIES_COUNT];
+        let mut page = [0u8; PAGE_SIZE];
+        for (index, (property, name_id)) in properties.iter().zip(&name_ids).enumerate() {
+            property_ids[index] = first_property_id + index as u64;
+
+            let definition = PropertyDefinition {
+                name_id: *name_id,
+                r#type: property.property_type(),
+                optional: property.is_optional() as u8,
+                ..Default::default()
+            };
+            let slot = KB1 + index * PROPERTY_DEFINITION_SIZE;
+            page[slot..slot + PROPERTY_DEFINITION_SIZE].copy_from_slice(&definition.serialize());
+        }
+
+        let raw_schema = match section {
+            Section::EdgeSchema => EdgeSchema {
+                id,
+                property_count: properties.len() as u16,
+                properties: property_ids,
+                ..Default::default()
+            }
+            .serialize(),
+            _ => NodeSchema {
+                id,
+                property_count: properties.len() as u16,
+                properties: property_ids,
+                ..Default::default()
+            }
+            .serialize(),
+        };
+        page[..KB1].copy_from_slice(&raw_schema);
+        self.write_page(page_offset, &page).await?;
+
+        for (index, property_id) in property_ids[..properties.len()].iter().enumerate() {
+            let offset = page_offset + (KB1 + index * PROPERTY_DEFINITION_SIZE) as u64;
+            self.insert_offset_item(
+                Section::SchemaProperties,
+                OffsetItem { id: *property_id, offset },
+            )
+            .await?;
+        }
+        self.insert_offset_item(section, OffsetItem { id, offset: page_offset }).await?;
+
+        Ok(id)
+    }
+
+    async fn read_schema(&self, section: Section, id: u64) -> Result<Option<[u8; KB1]>, StorageError> {
+        let Some((_, _, item)) = self.find_offset_item(section, id).await? else {
+            return Ok(None);
+        };
+
+        let page = self.read_page(item.offset).await?;
+        let mut raw = [0u8; KB1];
+        raw.copy_from_slice(&page[..KB1]);
+        Ok(Some(raw))
+    }
+
+    async fn load_property_builders(&self, property_ids: &[u64]) -> Result<Vec<PropertyBuilder>, StorageError> {
+        let mut properties = Vec::with_capacity(property_ids.len());
+        for property_id in property_ids {
+            let Some(definition) = self.get_property_definition(*property_id).await? else {
+                return Err(StorageError::NotFound(*property_id));
+            };
+            let name = self.resolve(definition.name_id)?;
+            properties.push(PropertyBuilder::new(
+                name,
+                definition.r#type,
+                definition.optional != 0,
+            ));
+        }
+        Ok(properties)
+    }
+}
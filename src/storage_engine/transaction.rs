@@ -0,0 +1,63 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+
+/// A group of mutations that reach the file together or not at all.
+///
+/// The whole engine API is available through the transaction, and reads see its own
+/// uncommitted writes. Pages written in the meantime are only staged; [`Transaction::commit`]
+/// logs and applies them as a single batch, while [`Transaction::rollback`], or dropping the
+/// transaction, discards them along with the in-memory changes.
+///
+/// A mutation failing after it wrote pages cannot be undone on its own and aborts the whole
+/// transaction: `commit` then rolls back and returns `StorageError::TransactionAborted`.
+#[derive(Debug)]
+pub struct Transaction<'a> {
+    engine: &'a mut StorageEngine,
+    finished: bool,
+}
+
+impl StorageEngine {
+    /// Starts a transaction. The engine is only reachable through it until it ends.
+    pub async fn begin(&mut self) -> Result<Transaction<'_>, StorageError> {
+        self.ensure_writable()?;
+        self.begin_batch();
+        Ok(Transaction { engine: self, finished: false })
+    }
+}
+
+impl Transaction<'_> {
+    /// Makes every change of the transaction durable.
+    pub async fn commit(mut self) -> Result<(), StorageError> {
+        self.finished = true;
+        self.engine.end_batch(true).await
+    }
+
+    /// Discards every change of the transaction.
+    pub fn rollback(mut self) {
+        self.finished = true;
+        self.engine.abort_batches();
+    }
+}
+
+impl Deref for Transaction<'_> {
+    type Target = StorageEngine;
+
+    fn deref(&self) -> &StorageEngine {
+        self.engine
+    }
+}
+
+impl DerefMut for Transaction<'_> {
+    fn deref_mut(&mut self) -> &mut StorageEngine {
+        self.engine
+    }
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.engine.abort_batches();
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::{Arc, MutexGuard};
+
+use crate::models::file_layout::{
+    bitmap_get, Edge, Node, PropertyDefinition, PropertyType, Section, MAX_PROPERTIES_COUNT, PROPERTY_BITMAP_SIZE,
+};
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+use crate::storage_engine::heap::{heap_class, is_heap_cell_offset};
+use crate::utils::sync::lock::lock_unpoisoned;
+
+/// Property definitions of the schemas loaded so far, by section and schema id.
+pub(crate) type SchemaCache = HashMap<(Section, u64), Arc<[PropertyDefinition]>>;
+
+/// Checks that `value` is a valid raw slot encoding for a property of `property_type`.
+///
+/// Integers are stored sign-extended, floats as their bit pattern in the low bits and
+/// booleans as 0 or 1; all unused high bits must be zero. Variable-length values hold the
+/// offset of a cell of their heap class, which [`StorageEngine::heap_cell_violation`] then
+/// checks is allocated.
+pub(crate) fn check_raw_value(property_type: PropertyType, value: u64) -> Result<(), String> {
+    let signed = value as i64;
+    let fits = match property_type {
+        PropertyType::Int8 => i8::try_from(signed).is_ok(),
+        PropertyType::Int16 => i16::try_from(signed).is_ok(),
+        PropertyType::Int32 => i32::try_from(signed).is_ok(),
+        PropertyType::Int64 | PropertyType::Float64 => true,
+        PropertyType::Float8 => value <= u8::MAX as u64,
+        PropertyType::Float16 => value <= u16::MAX as u64,
+        PropertyType::Float32 => value <= u32::MAX as u64,
+        PropertyType::Bool => value <= 1,
+        PropertyType::String32
+        | PropertyType::String64
+        | PropertyType::String512
+        | PropertyType::Page => heap_class(property_type).is_some_and(|class| is_heap_cell_offset(class, value)),
+        PropertyType::InvalidType => return Err("schema declares an invalid property type".to_string()),
+    };
+
+    if fits {
+        Ok(())
+    } else {
+        Err(format!("value {value:#x} is not encodable as {property_type:?}"))
+    }
+}
+
+impl StorageEngine {
+    pub(crate) fn cached_schemas(&self) -> MutexGuard<'_, SchemaCache> {
+        lock_unpoisoned(&self.schema_cache)
+    }
+
+    /// Returns the property definitions of a schema in slot order, caching them after the first load.
+    pub(crate) async fn schema_definitions(&self, section: Section, schema_id: u64) -> Result<Arc<[PropertyDefinition]>, StorageError> {
+        if let Some(definitions) = self.cached_schemas().get(&(section, schema_id)) {
+            return Ok(definitions.clone());
+        }
+
+        let property_ids = match section {
+            Section::EdgeSchema => self
+                .get_edge_schema(schema_id)
+                .await?
+                .map(|schema| schema.properties[..schema.property_count as usize].to_vec()),
+            _ => self
+                .get_node_schema(schema_id)
+                .await?
+                .map(|schema| schema.properties[..schema.property_count as usize].to_vec()),
+        };
+        let Some(property_ids) = property_ids else {
+            return Err(StorageError::UnknownSchema(schema_id));
+        };
+
+        let mut definitions = Vec::with_capacity(property_ids.len());
+        for property_id in property_ids {
+            match self.get_property_definition(property_id).await? {
+                Some(definition) => definitions.push(definition),
+                None => return Err(StorageError::NotFound(property_id)),
+            }
+        }
+
+        let definitions: Arc<[PropertyDefinition]> = definitions.into();
+        self.cached_schemas().insert((section, schema_id), definitions.clone());
+        Ok(definitions)
+    }
+
+    pub(crate) async fn validate_node(&mut self, node: &Node) -> Result<(), StorageError> {
+        self.validate_properties(Section::NodeSchema, node.schema_id, &node.property_values, &node.present)
+            .await
+    }
+
+    pub(crate) async fn validate_edge(&mut self, edge: &Edge) -> Result<(), StorageError> {
+        self.validate_properties(Section::EdgeSchema, edge.schema_id, &edge.property_values, &edge.present)
+            .await
+    }
+
+    async fn validate_properties(
+        &mut self,
+        section: Section,
+        schema_id: u64,
+        values: &[u64; MAX_PROPERTIES_COUNT],
+        present: &[u8; PROPERTY_BITMAP_SIZE],
+    ) -> Result<(), StorageError> {
+        let definitions = self.schema_definitions(section, schema_id).await?;
+
+        for (index, definition) in definitions.iter().enumerate() {
+            let property_type = definition.r#type;
+            let violation = if !bitmap_get(present, index) {
+                (definition.optional == 0).then(|| "required property is missing".to_string())
+            } else if let Err(reason) = check_raw_value(property_type, values[index]) {
+                Some(reason)
+            } else if let Some(class) = heap_class(property_type) {
+                self.heap_cell_violation(class, values[index]).await?
+            } else {
+                None
+            };
+
+            if let Some(reason) = violation {
+                return Err(StorageError::SchemaViolation {
+                    property: self.resolve(definition.name_id).unwrap_or_else(|_| format!("#{index}")),
+                    reason,
+                });
+            }
+        }
+
+        for (index, value) in values.iter().enumerate().skip(definitions.len()) {
+            if bitmap_get(present, index) || *value != 0 {
+                return Err(StorageError::SchemaViolation {
+                    property: format!("#{index}"),
+                    reason: format!("schema {schema_id} only declares {} properties", definitions.len()),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
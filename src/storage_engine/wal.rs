@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use tokio::fs::{File, OpenOptions as FileOpenOptions};
+use tokio::io::{self, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+
+use crate::models::file_layout::{NexoraFile, FILE_FORMAT_VERSION, PAGE_SIZE};
+use crate::storage_engine::engine::{StorageEngine, StorageError};
+use crate::storage_engine::id_map::{IdMap, IdMapMark};
+use crate::storage_engine::name_table::{NameTable, NameTableMark};
+use crate::utils::checksum::crc32c::crc32c;
+use crate::utils::encoding::endian::endian::{read_bytes, read_u32_le, read_u64_le, write_u32_le, write_u64_le};
+
+/// Marks the start of every batch in the log.
+const WAL_BATCH_MAGIC: u32 = u32::from_le_bytes(*b"NXWL");
+/// magic (4) + lsn (8) + page count (4).
+const WAL_BATCH_HEADER_SIZE: usize = 16;
+/// Page offset (8) followed by the page image.
+const WAL_PAGE_RECORD_SIZE: usize = 8 + PAGE_SIZE;
+/// CRC32C of the whole batch, closing it.
+const WAL_BATCH_TRAILER_SIZE: usize = 4;
+
+pub(crate) type PageImages = BTreeMap<u64, Box<[u8; PAGE_SIZE]>>;
+
+/// -------------------- Write-ahead log --------------------
+/// Pages are never written straight to the data file. Mutations stage page images in a
+/// batch, as dirty frames of the buffer pool; committing the outermost batch appends them
+/// to the log next to the `.nexora` file, syncs the log and only then writes them back to
+/// the data file. A batch found complete
+/// in the log when the engine loads is applied again, so a crash mid-apply is redone.
+///
+/// A batch is committed once the log is synced. Should writing it back fail after that, it
+/// stays in the log for the next load to redo; until then the engine reads its pages from
+/// memory and refuses every write.
+#[derive(Debug, Default)]
+pub struct Wal {
+    handle: Option<File>,
+    /// One per open batch, innermost last.
+    savepoints: Vec<Savepoint>,
+    /// Pages recovered by a read-only engine, which cannot apply them to the file, or
+    /// committed by a batch that failed to write them back.
+    recovered: PageImages,
+    /// Pages staged since the engine was opened, telling whether a failed batch wrote any.
+    writes: u64,
+    /// Set when a nested batch failed after staging pages; the outermost one can then only abort.
+    poisoned: bool,
+    /// Why a committed batch could not be written back, leaving the data file to be recovered.
+    write_back_error: Option<String>,
+    lsn: u64,
+}
+
+/// In-memory state to restore when a batch is abandoned.
+#[derive(Debug)]
+struct Savepoint {
+    layout: Box<NexoraFile>,
+    names: NameTableMark,
+    ids: IdMapMark,
+    writes: u64,
+}
+
+impl Wal {
+    /// Sequence number of the last committed batch.
+    pub fn lsn(&self) -> u64 {
+        self.lsn
+    }
+
+    /// The log state a snapshot reads with: no handle, only the pages recovered or committed
+    /// and never applied.
+    pub(crate) fn for_snapshot(&self) -> Wal {
+        Wal { recovered: self.recovered.clone(), lsn: self.lsn, ..Wal::default() }
+    }
+}
+
+/// Path of the log belonging to the database file at `file_path`.
+pub fn wal_path(file_path: &Path) -> PathBuf {
+    let mut path = file_path.as_os_str().to_owned();
+    path.push("-wal");
+    PathBuf::from(path)
+}
+
+fn encode_batch(lsn: u64, pages: &PageImages) -> Vec<u8> {
+    let len = WAL_BATCH_HEADER_SIZE + pages.len() * WAL_PAGE_RECORD_SIZE + WAL_BATCH_TRAILER_SIZE;
+    let mut buf = vec![0u8; len];
+
+    write_u32_le(WAL_BATCH_MAGIC, &mut buf[0..4]);
+    write_u64_le(lsn, &mut buf[4..12]);
+    write_u32_le(pages.len() as u32, &mut buf[12..16]);
+
+    let mut offset = WAL_BATCH_HEADER_SIZE;
+    for (page_offset, page) in pages {
+        write_u64_le(*page_offset, &mut buf[offset..offset + 8]);
+        buf[offset + 8..offset + WAL_PAGE_RECORD_SIZE].copy_from_slice(&page[..]);
+        offset += WAL_PAGE_RECORD_SIZE;
+    }
+
+    let checksum = crc32c(&buf[..offset]);
+    write_u32_le(checksum, &mut buf[offset..offset + WAL_BATCH_TRAILER_SIZE]);
+    buf
+}
+
+/// Decodes the batch starting at `start`, returning its lsn, pages and length.
+///
+/// A torn or corrupted batch ends the log: it was never acknowledged as committed.
+fn decode_batch(buf: &[u8], start: usize) -> Option<(u64, PageImages, usize)> {
+    let batch = buf.get(start..)?;
+    if read_u32_le(batch, 0)? != WAL_BATCH_MAGIC {
+        return None;
+    }
+    let lsn = read_u64_le(batch, 4)?;
+    let nb_pages = read_u32_le(batch, 12)? as usize;
+
+    let body_len = nb_pages.checked_mul(WAL_PAGE_RECORD_SIZE)?.checked_add(WAL_BATCH_HEADER_SIZE)?;
+    if read_u32_le(batch, body_len)? != crc32c(read_bytes(batch, 0, body_len)?) {
+        return None;
+    }
+
+    let mut pages = PageImages::new();
+    for index in 0..nb_pages {
+        let record = WAL_BATCH_HEADER_SIZE + index * WAL_PAGE_RECORD_SIZE;
+        let mut page = Box::new([0u8; PAGE_SIZE]);
+        page.copy_from_slice(read_bytes(batch, record + 8, PAGE_SIZE)?);
+        pages.insert(read_u64_le(batch, record)?, page);
+    }
+
+    Some((lsn, pages, body_len + WAL_BATCH_TRAILER_SIZE))
+}
+
+impl StorageEngine {
+    /// Runs `operation` as a single batch: its page writes reach the data file together
+    /// once it succeeds, and are discarded along with in-memory changes if it fails.
+    ///
+    /// Nested calls join the outermost batch.
+    pub(crate) async fn atomically<T>(
+        &mut self,
+        operation: impl AsyncFnOnce(&mut Self) -> Result<T, StorageError>,
+    ) -> Result<T, StorageError> {
+        self.begin_batch();
+        // Boxed so that nested batches do not inline each other's futures.
+        let result = Box::pin(operation(self)).await;
+        self.end_batch(result.is_ok()).await?;
+        result
+    }
+
+    pub(crate) fn begin_batch(&mut self) {
+        self.wal.savepoints.push(Savepoint {
+            layout: Box::new(self.file_layout),
+            names: self.name_table.mark(),
+            ids: self.id_map.as_ref().map(IdMap::mark).unwrap_or_default(),
+            writes: self.wal.writes,
+        });
+    }
+
+    /// Whether a batch is open, as inside a transaction.
+    pub(crate) fn in_batch(&self) -> bool {
+        !self.wal.savepoints.is_empty()
+    }
+
+    /// Leaves a batch, committing or aborting it if it is the outermost one.
+    ///
+    /// A nested batch failing before it staged any page is undone on its own. One that did
+    /// stage pages cannot be separated from the rest, so the outermost batch aborts as well.
+    pub(crate) async fn end_batch(&mut self, commit: bool) -> Result<(), StorageError> {
+        let Some(savepoint) = self.wal.savepoints.pop() else {
+            return Ok(());
+        };
+        let nested = self.in_batch();
+
+        if !commit && (!nested || savepoint.writes == self.wal.writes) {
+            self.restore_savepoint(savepoint);
+            return Ok(());
+        }
+        if nested {
+            self.wal.poisoned |= !commit;
+            return Ok(());
+        }
+        if self.wal.poisoned {
+            self.restore_savepoint(savepoint);
+            return Err(StorageError::TransactionAborted);
+        }
+
+        let result = self.commit_batch().await;
+        if result.is_err() {
+            self.restore_savepoint(savepoint);
+        } else if let Some(id_map) = &mut self.id_map {
+            id_map.commit();
+        }
+        result
+    }
+
+    /// Abandons every open batch, discarding their pages and in-memory changes.
+    pub(crate) fn abort_batches(&mut self) {
+        let mut savepoints = std::mem::take(&mut self.wal.savepoints);
+        savepoints.truncate(1);
+        if let Some(savepoint) = savepoints.pop() {
+            self.restore_savepoint(savepoint);
+        }
+    }
+
+    /// Layout as of the last commit, leaving out the open batches.
+    pub(crate) fn committed_layout(&self) -> &NexoraFile {
+        match self.wal.savepoints.first() {
+            Some(savepoint) => &savepoint.layout,
+            None => &self.file_layout,
+        }
+    }
+
+    /// Layout and names as of the last commit, leaving out the open batches.
+    pub(crate) fn committed_state(&self) -> (NexoraFile, NameTable) {
+        let mut names = self.name_table.clone();
+        if let Some(savepoint) = self.wal.savepoints.first() {
+            names.rollback(savepoint.names);
+        }
+        (*self.committed_layout(), names)
+    }
+
+    fn restore_savepoint(&mut self, savepoint: Savepoint) {
+        self.file_layout = *savepoint.layout;
+        self.name_table.rollback(savepoint.names);
+        if let Some(id_map) = &mut self.id_map {
+            id_map.rollback(savepoint.ids);
+        }
+        if !self.in_batch() {
+            self.pool().discard_dirty();
+            self.wal.poisoned = false;
+            // Definitions cached from a schema registered in the batch are gone with it.
+            self.cached_schemas().clear();
+        }
+    }
+
+    /// Stages a page image in the current batch, committing it right away outside of one.
+    pub(crate) async fn stage_page(&mut self, offset: u64, page: [u8; PAGE_SIZE]) -> Result<(), StorageError> {
+        self.pool().write(offset, &page);
+        self.wal.writes += 1;
+        if !self.in_batch() {
+            return self.commit_batch().await;
+        }
+        Ok(())
+    }
+
+    /// The image of a page a read-only engine recovered from the log, if any.
+    pub(crate) fn recovered_page(&self, offset: u64) -> Option<&[u8; PAGE_SIZE]> {
+        self.wal.recovered.get(&offset).map(|page| &**page)
+    }
+
+    /// Length of the file once the pages not yet applied to it are, as a read-only engine
+    /// holding recovered pages past the end of the data file sees it.
+    pub(crate) async fn logical_file_len(&self) -> Result<u64, StorageError> {
+        let file_len = self.file_handle.metadata().await?.len();
+        let recovered_end = self.wal.recovered.keys().map(|offset| offset + PAGE_SIZE as u64).max();
+        let staged_end = self.pool().dirty_end().max(recovered_end).unwrap_or(0);
+        Ok(file_len.max(staged_end))
+    }
+
+    /// Logs the dirty pages, syncs the log, then writes them back and syncs the data file.
+    ///
+    /// The pages are dropped from the pool if that fails, like those of an aborted batch.
+    async fn commit_batch(&mut self) -> Result<(), StorageError> {
+        let pages = self.pool().dirty_pages();
+        if pages.is_empty() {
+            return Ok(());
+        }
+
+        let result = self.write_batch(pages).await;
+        match result {
+            Ok(()) => self.pool().mark_clean(),
+            Err(_) => self.pool().discard_dirty(),
+        }
+        result
+    }
+
+    /// Commits `pages`, failing only if the batch did not make it to the log.
+    async fn write_batch(&mut self, pages: PageImages) -> Result<(), StorageError> {
+        let lsn = self.wal.lsn + 1;
+        if let Err(err) = self.append_batch(lsn, &pages).await {
+            // Whatever part of the batch reached the log must not be redone on the next load.
+            if let Some(handle) = self.wal.handle.as_mut()
+                && let Err(truncate_err) = handle.set_len(0).await
+            {
+                self.wal.write_back_error = Some(truncate_err.to_string());
+            }
+            return Err(err);
+        }
+        self.wal.lsn = lsn;
+
+        if let Err(err) = self.write_back(lsn, &pages).await {
+            self.wal.recovered.extend(pages);
+            self.wal.write_back_error = Some(err.to_string());
+        }
+        Ok(())
+    }
+
+    /// Replaces the content of the log with the batch and syncs it.
+    async fn append_batch(&mut self, lsn: u64, pages: &PageImages) -> Result<(), StorageError> {
+        let batch = encode_batch(lsn, pages);
+        let Some(handle) = self.wal.handle.as_mut() else {
+            return Err(StorageError::ReadOnly);
+        };
+        handle.set_len(0).await?;
+        handle.seek(SeekFrom::Start(0)).await?;
+        handle.write_all(&batch).await?;
+        handle.flush().await?;
+        handle.sync_data().await?;
+        Ok(())
+    }
+
+    /// Writes a committed batch back to the data file, then empties the log.
+    async fn write_back(&mut self, lsn: u64, pages: &PageImages) -> Result<(), StorageError> {
+        self.preserve_versions(lsn, pages.keys().copied()).await?;
+        self.reserve_file_space().await?;
+        self.apply_pages(pages).await?;
+
+        // Every page is in the data file now; an unsynced truncation only means the batch
+        // gets applied once more on the next load.
+        if let Some(handle) = self.wal.handle.as_mut() {
+            handle.set_len(0).await?;
+        }
+        Ok(())
+    }
+
+    /// Fails once a committed batch could not be written back: the data file may be torn
+    /// until the engine is reopened and redoes the batch from the log.
+    pub(crate) fn ensure_written_back(&self) -> Result<(), StorageError> {
+        match &self.wal.write_back_error {
+            Some(reason) => Err(StorageError::RecoveryRequired(reason.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Grows the data file up to the end of the allocated pages, so that extensions claim
+    /// their disk space up front. The file may lag behind after a crash; pages past its end
+    /// are reserved ones, never read before being written.
+    async fn reserve_file_space(&mut self) -> Result<(), StorageError> {
+        let end_offset = self.file_layout.header.end_offset;
+        if self.file_handle.metadata().await?.len() < end_offset {
+            self.file_handle.set_len(end_offset).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes `pages` to the data file, each run of adjacent pages with a single write.
+    async fn apply_pages(&mut self, pages: &PageImages) -> Result<(), StorageError> {
+        let mut pages = pages.iter().peekable();
+        while let Some((&start, page)) = pages.next() {
+            let mut run = page.to_vec();
+            while let Some((_, page)) = pages.next_if(|(offset, _)| **offset == start + run.len() as u64) {
+                run.extend_from_slice(&page[..]);
+            }
+            self.file_handle.write_all_at(start, run).await?;
+        }
+        self.file_handle.sync_data().await?;
+        Ok(())
+    }
+
+    /// Opens the log of a writable engine, creating it if needed.
+    pub(crate) async fn open_wal(&mut self) -> Result<(), StorageError> {
+        let handle = FileOpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(wal_path(Path::new(&self.file_path)))
+            .await?;
+        self.wal.handle = Some(handle);
+        Ok(())
+    }
+
+    /// Drops whatever a previous database left in the log, before a fresh file is written.
+    pub(crate) async fn reset_wal(&mut self) -> Result<(), StorageError> {
+        if let Some(handle) = self.wal.handle.as_mut() {
+            handle.set_len(0).await?;
+            handle.sync_data().await?;
+        }
+        Ok(())
+    }
+
+    /// Redoes every complete batch found in the log.
+    ///
+    /// A writable engine applies them to the data file and empties the log; a read-only one
+    /// keeps them in memory and reads through them instead. A log next to a file of a newer
+    /// version is left alone, as it may not be in a format this build knows.
+    pub(crate) async fn recover_wal(&mut self) -> Result<(), StorageError> {
+        let mut raw = Vec::new();
+        match self.wal.handle.as_mut() {
+            Some(handle) => {
+                handle.seek(SeekFrom::Start(0)).await?;
+                handle.read_to_end(&mut raw).await?;
+            }
+            None => match File::open(wal_path(Path::new(&self.file_path))).await {
+                Ok(mut handle) => {
+                    handle.read_to_end(&mut raw).await?;
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            },
+        }
+
+        if raw.is_empty() {
+            // Whatever an earlier recovery found has been applied and checkpointed since.
+            self.wal.recovered = PageImages::new();
+            self.wal.lsn = 0;
+            return Ok(());
+        }
+        if let Some(found) = self.stored_version().await?
+            && found > FILE_FORMAT_VERSION
+        {
+            return Err(StorageError::NewerLog { found });
+        }
+
+        let mut recovered = PageImages::new();
+        let mut start = 0;
+        while let Some((lsn, pages, len)) = decode_batch(&raw, start) {
+            recovered.extend(pages);
+            self.wal.lsn = lsn;
+            start += len;
+        }
+
+        if self.read_only {
+            self.wal.recovered = recovered;
+            return Ok(());
+        }
+        if !recovered.is_empty() {
+            self.apply_pages(&recovered).await?;
+        }
+        self.reset_wal().await
+    }
+}
@@ -0,0 +1 @@
+pub mod crc32c;
@@ -2,6 +2,10 @@ pub fn write_u64_le(value: u64, buf: &mut [u8]) {
     buf[..8].copy_from_slice(&value.to_le_bytes());
 }
 
+pub fn write_u32_le(value: u32, buf: &mut [u8]) {
+    buf[..4].copy_from_slice(&value.to_le_bytes());
+}
+
 pub fn write_u16_le(value: u16, buf: &mut [u8]) {
     buf[..2].copy_from_slice(&value.to_le_bytes());
 }
@@ -20,6 +24,11 @@ pub fn read_u64_le(buf: &[u8], offset: usize) -> Option<u64> {
         .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
 }
 
+pub fn read_u32_le(buf: &[u8], offset: usize) -> Option<u32> {
+    buf.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
 pub fn read_u16_le(buf: &[u8], offset: usize) -> Option<u16> {
     buf.get(offset..offset + 2)
         .map(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
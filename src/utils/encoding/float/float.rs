@@ -0,0 +1,165 @@
+/// -------------------- F16 --------------------
+/// IEEE 754 binary16: 1 sign bit, 5 exponent bits (bias 15), 10 mantissa bits.
+///
+/// Conversion from `f32` rounds to nearest, ties to even. Magnitudes that round past
+/// 65504 become infinity, magnitudes below half of the smallest subnormal (2^-24) become
+/// a signed zero, and NaN payloads keep their top 10 bits (always staying NaN).
+/// Equality compares bit patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct F16(u16);
+
+impl F16 {
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    pub fn is_nan(self) -> bool {
+        self.0 & 0x7c00 == 0x7c00 && self.0 & 0x03ff != 0
+    }
+
+    pub fn from_f32(value: f32) -> Self {
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        let exponent = ((bits >> 23) & 0xff) as i32;
+        let mantissa = bits & 0x007f_ffff;
+
+        if exponent == 0xff {
+            return if mantissa == 0 {
+                Self(sign | 0x7c00)
+            } else {
+                Self(sign | 0x7e00 | (mantissa >> 13) as u16)
+            };
+        }
+
+        let unbiased = exponent - 127;
+        if unbiased > 15 {
+            return Self(sign | 0x7c00);
+        }
+
+        if unbiased >= -14 {
+            // Normal range: drop 13 mantissa bits. A carry out of the mantissa correctly
+            // bumps the exponent, up to infinity.
+            let half = (((unbiased + 15) as u32) << 10) | (mantissa >> 13);
+            return Self(sign | round_shifted(half, mantissa & 0x1fff, 13) as u16);
+        }
+
+        // Subnormal range: value = m * 2^-24.
+        let shift = (-unbiased - 1) as u32;
+        if exponent == 0 || shift > 24 {
+            return Self(sign);
+        }
+        let full = mantissa | 0x0080_0000;
+        Self(sign | round_shifted(full >> shift, full & ((1 << shift) - 1), shift) as u16)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        let sign = ((self.0 & 0x8000) as u32) << 16;
+        let exponent = ((self.0 >> 10) & 0x1f) as u32;
+        let mantissa = (self.0 & 0x03ff) as u32;
+
+        match exponent {
+            0 => {
+                let magnitude = mantissa as f32 * f32::from_bits(0x3380_0000); // 2^-24
+                f32::from_bits(sign | magnitude.to_bits())
+            }
+            0x1f => f32::from_bits(sign | 0x7f80_0000 | (mantissa << 13)),
+            _ => f32::from_bits(sign | ((exponent + 112) << 23) | (mantissa << 13)),
+        }
+    }
+}
+
+impl From<F16> for f32 {
+    fn from(value: F16) -> Self {
+        value.to_f32()
+    }
+}
+
+/// -------------------- F8E4M3 --------------------
+/// 8-bit minifloat in the E4M3 (FN) layout: 1 sign bit, 4 exponent bits (bias 7),
+/// 3 mantissa bits. There are no infinities and `S.1111.111` is the only NaN.
+///
+/// Conversion from `f32` rounds to nearest, ties to even. Magnitudes above the largest
+/// finite value (448), infinities included, saturate to ±448; magnitudes below half of
+/// the smallest subnormal (2^-9) become a signed zero. Equality compares bit patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct F8E4M3(u8);
+
+impl F8E4M3 {
+    pub const MAX: f32 = 448.0;
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_nan(self) -> bool {
+        self.0 & 0x7f == 0x7f
+    }
+
+    pub fn from_f32(value: f32) -> Self {
+        let bits = value.to_bits();
+        let sign = ((bits >> 24) & 0x80) as u8;
+
+        if value.is_nan() {
+            return Self(sign | 0x7f);
+        }
+        if value.abs() > Self::MAX {
+            return Self(sign | 0x7e);
+        }
+
+        let exponent = ((bits >> 23) & 0xff) as i32;
+        let mantissa = bits & 0x007f_ffff;
+        let unbiased = exponent - 127;
+
+        if unbiased >= -6 {
+            let quarter = (((unbiased + 7) as u32) << 3) | (mantissa >> 20);
+            return Self(sign | round_shifted(quarter, mantissa & 0x000f_ffff, 20) as u8);
+        }
+
+        // Subnormal range: value = m * 2^-9.
+        let shift = (14 - unbiased) as u32;
+        if exponent == 0 || shift > 24 {
+            return Self(sign);
+        }
+        let full = mantissa | 0x0080_0000;
+        Self(sign | round_shifted(full >> shift, full & ((1 << shift) - 1), shift) as u8)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        let sign = ((self.0 & 0x80) as u32) << 24;
+        let exponent = ((self.0 >> 3) & 0x0f) as u32;
+        let mantissa = (self.0 & 0x07) as u32;
+
+        if self.is_nan() {
+            return f32::from_bits(sign | 0x7fc0_0000);
+        }
+        if exponent == 0 {
+            let magnitude = mantissa as f32 * f32::from_bits(0x3b00_0000); // 2^-9
+            return f32::from_bits(sign | magnitude.to_bits());
+        }
+        f32::from_bits(sign | ((exponent + 120) << 23) | (mantissa << 20))
+    }
+}
+
+impl From<F8E4M3> for f32 {
+    fn from(value: F8E4M3) -> Self {
+        value.to_f32()
+    }
+}
+
+/// Rounds `truncated` to nearest, ties to even, given the `shift` low bits that were dropped.
+fn round_shifted(truncated: u32, dropped: u32, shift: u32) -> u32 {
+    let halfway = 1 << (shift - 1);
+    if dropped > halfway || (dropped == halfway && truncated & 1 == 1) {
+        truncated + 1
+    } else {
+        truncated
+    }
+}
@@ -1 +1,2 @@
 pub mod endian;
+pub mod float;
@@ -1 +1 @@
-pub mod crud;
+pub mod positional;
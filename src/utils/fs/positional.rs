@@ -0,0 +1,115 @@
+use std::fs::{File, Metadata};
+use std::io;
+use std::sync::Arc;
+
+use memmap2::Mmap;
+use tokio::task::spawn_blocking;
+
+/// A file read and written at explicit offsets instead of through a shared cursor, so any
+/// number of tasks can use it at once. Clones share the same underlying file.
+///
+/// Its async calls run on the blocking thread pool, as the `tokio::fs` operations do.
+#[derive(Debug, Clone)]
+pub struct PositionalFile {
+    file: Arc<File>,
+}
+
+impl PositionalFile {
+    pub fn new(file: File) -> Self {
+        Self { file: Arc::new(file) }
+    }
+
+    /// Reads exactly `N` bytes starting at `offset`, failing with `UnexpectedEof` past the end.
+    pub async fn read_exact_at<const N: usize>(&self, offset: u64) -> io::Result<[u8; N]> {
+        self.blocking(move |file| {
+            let mut buf = [0u8; N];
+            read_exact_at(file, &mut buf, offset)?;
+            Ok(buf)
+        })
+        .await
+    }
+
+    /// Writes the whole of `buf` starting at `offset`.
+    pub async fn write_all_at(&self, offset: u64, buf: Vec<u8>) -> io::Result<()> {
+        self.blocking(move |file| write_all_at(file, &buf, offset)).await
+    }
+
+    pub async fn metadata(&self) -> io::Result<Metadata> {
+        self.blocking(File::metadata).await
+    }
+
+    pub async fn set_len(&self, len: u64) -> io::Result<()> {
+        self.blocking(move |file| file.set_len(len)).await
+    }
+
+    pub async fn sync_data(&self) -> io::Result<()> {
+        self.blocking(File::sync_data).await
+    }
+
+    /// Maps the whole file read-only, as long as it is when called.
+    ///
+    /// # Safety
+    ///
+    /// The mapping must not be read past the end of the file: pages cut off by a later
+    /// truncation fault on access.
+    pub unsafe fn map(&self) -> io::Result<Mmap> {
+        unsafe { Mmap::map(&*self.file) }
+    }
+
+    async fn blocking<T: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&File) -> io::Result<T> + Send + 'static,
+    ) -> io::Result<T> {
+        let file = self.file.clone();
+        match spawn_blocking(move || operation(&file)).await {
+            Ok(result) => result,
+            Err(err) => Err(io::Error::other(err)),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+#[cfg(unix)]
+fn write_all_at(file: &File, buf: &[u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::write_all_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => {
+                buf = &mut buf[read..];
+                offset += read as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn write_all_at(file: &File, mut buf: &[u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+
+    while !buf.is_empty() {
+        match file.seek_write(buf, offset) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => {
+                buf = &buf[written..];
+                offset += written as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
@@ -1,2 +1,4 @@
+pub mod checksum;
 pub mod fs;
 pub mod encoding;
+pub mod sync;
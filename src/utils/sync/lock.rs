@@ -0,0 +1,11 @@
+use std::sync::{Mutex, MutexGuard};
+
+/// -------------------- Unpoisoned locks --------------------
+/// Locks `mutex`, ignoring poisoning.
+///
+/// The engine's mutexes only guard caches and bookkeeping that are changed in short sections
+/// without fallible calls, so a panic elsewhere while one is held cannot leave its state half
+/// updated; refusing the lock afterwards would only turn that panic into more of them.
+pub fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,126 @@
+use nexora_rs::models::file_layout::{Node, PAGE_SIZE};
+use nexora_rs::models::schema_builder::builder::NodeSchemaBuilder;
+use nexora_rs::storage_engine::buffer_pool::DEFAULT_BUFFER_POOL_PAGES;
+use nexora_rs::storage_engine::engine::StorageEngine;
+
+mod common;
+use common::TempPath;
+
+/// Reopens the database at `path` with a buffer pool of `pages`.
+async fn open_with_pool(path: &TempPath, pages: usize) -> StorageEngine {
+    StorageEngine::options().buffer_pool_pages(pages).open(path).await.unwrap()
+}
+
+/// A database at a path unique to the test holding node schema 1, and three pages of it
+/// that opening it leaves unread: the last ones the allocator reserved.
+async fn fresh_database(name: &str) -> (TempPath, [u64; 3]) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    assert!(engine.page_stats().reserved_pages >= 3);
+    let end = engine.file_layout.header.end_offset;
+    let pages = [1, 2, 3].map(|index| end - index * PAGE_SIZE as u64);
+    (path, pages)
+}
+
+/// Reads the page at `offset` through the buffer pool.
+async fn read(engine: &StorageEngine, offset: u64) {
+    engine.pin_page(offset).await.unwrap();
+    engine.unpin_page(offset);
+}
+
+#[tokio::test]
+async fn least_recently_used_pages_are_evicted_first() {
+    let (path, [a, b, c]) = fresh_database("lru").await;
+    let engine = open_with_pool(&path, 2).await;
+    read(&engine, a).await;
+    read(&engine, b).await;
+    let before = engine.buffer_pool_stats();
+    assert_eq!(before.resident_pages, 2);
+
+    // Reading `a` again leaves `b` as the least recently used page.
+    read(&engine, a).await;
+    read(&engine, c).await;
+    read(&engine, a).await;
+    read(&engine, b).await;
+    let after = engine.buffer_pool_stats();
+    assert_eq!(after.hits - before.hits, 2);
+    assert_eq!(after.misses - before.misses, 2);
+    assert_eq!(after.evictions - before.evictions, 2);
+    assert_eq!(after.resident_pages, 2);
+
+    // `c` went when `b` came back.
+    read(&engine, a).await;
+    read(&engine, c).await;
+    let last = engine.buffer_pool_stats();
+    assert_eq!((last.hits - after.hits, last.misses - after.misses), (1, 1));
+}
+
+#[tokio::test]
+async fn pinned_pages_outlive_eviction() {
+    let (path, [a, b, c]) = fresh_database("pinned").await;
+    let engine = open_with_pool(&path, 1).await;
+    engine.pin_page(a).await.unwrap();
+    engine.pin_page(a).await.unwrap();
+
+    // The pinned page fills the pool, so pages read since are evicted straight away.
+    read(&engine, b).await;
+    read(&engine, c).await;
+    let stats = engine.buffer_pool_stats();
+    assert_eq!((stats.pinned_pages, stats.resident_pages), (1, 1));
+    let hits = stats.hits;
+    read(&engine, a).await;
+    assert_eq!(engine.buffer_pool_stats().hits, hits + 1);
+
+    // Pins nest: the page can only go once the last one is released.
+    engine.unpin_page(a);
+    read(&engine, b).await;
+    assert_eq!(engine.buffer_pool_stats().pinned_pages, 1);
+    engine.unpin_page(a);
+    read(&engine, b).await;
+    let misses = engine.buffer_pool_stats().misses;
+    read(&engine, a).await;
+    let stats = engine.buffer_pool_stats();
+    assert_eq!((stats.pinned_pages, stats.resident_pages, stats.misses), (0, 1, misses + 1));
+}
+
+#[tokio::test]
+async fn dirty_pages_stay_until_their_batch_commits() {
+    let (path, _) = fresh_database("dirty").await;
+    let mut engine = open_with_pool(&path, 1).await;
+
+    let mut transaction = engine.begin().await.unwrap();
+    for id in 1..=300 {
+        transaction.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    let stats = transaction.buffer_pool_stats();
+    assert!(stats.dirty_pages > stats.capacity);
+    assert!(stats.resident_pages >= stats.dirty_pages);
+    assert_eq!(transaction.get_node(1).await.unwrap().unwrap().id, 1);
+    transaction.commit().await.unwrap();
+
+    let stats = engine.buffer_pool_stats();
+    assert_eq!((stats.dirty_pages, stats.resident_pages), (0, 1));
+    assert_eq!(engine.scan_nodes(..).await.unwrap().len(), 300);
+}
+
+#[tokio::test]
+async fn capacity_is_set_when_opening() {
+    let (path, [a, _, _]) = fresh_database("capacity").await;
+    let engine = StorageEngine::load(&path).await.unwrap();
+    assert_eq!(engine.buffer_pool_stats().capacity, DEFAULT_BUFFER_POOL_PAGES);
+    drop(engine);
+
+    let engine = open_with_pool(&path, 16).await;
+    assert_eq!(engine.buffer_pool_stats().capacity, 16);
+    drop(engine);
+
+    // An empty pool reads every page from the file.
+    let engine = open_with_pool(&path, 0).await;
+    let before = engine.buffer_pool_stats();
+    read(&engine, a).await;
+    read(&engine, a).await;
+    let after = engine.buffer_pool_stats();
+    assert_eq!((after.capacity, after.resident_pages), (0, 0));
+    assert_eq!((after.hits - before.hits, after.misses - before.misses), (0, 2));
+}
@@ -0,0 +1,31 @@
+// Only the test files that write logs by hand use these.
+#![allow(dead_code)]
+
+use nexora_rs::models::file_layout::PAGE_SIZE;
+use nexora_rs::utils::checksum::crc32c::crc32c;
+
+const WAL_BATCH_MAGIC: &[u8; 4] = b"NXWL";
+
+/// Pages of `after` that differ from `before`, by offset.
+pub fn changed_pages(before: &[u8], after: &[u8]) -> Vec<(u64, Vec<u8>)> {
+    after
+        .chunks(PAGE_SIZE)
+        .enumerate()
+        .filter(|(index, page)| before.get(index * PAGE_SIZE..(index + 1) * PAGE_SIZE) != Some(*page))
+        .map(|(index, page)| ((index * PAGE_SIZE) as u64, page.to_vec()))
+        .collect()
+}
+
+/// A log batch in the on-disk format: magic, lsn, page count, the pages and a CRC32C.
+pub fn encode_batch(lsn: u64, pages: &[(u64, Vec<u8>)]) -> Vec<u8> {
+    let mut batch = WAL_BATCH_MAGIC.to_vec();
+    batch.extend_from_slice(&lsn.to_le_bytes());
+    batch.extend_from_slice(&(pages.len() as u32).to_le_bytes());
+    for (offset, page) in pages {
+        batch.extend_from_slice(&offset.to_le_bytes());
+        batch.extend_from_slice(page);
+    }
+    let checksum = crc32c(&batch);
+    batch.extend_from_slice(&checksum.to_le_bytes());
+    batch
+}
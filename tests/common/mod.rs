@@ -0,0 +1,48 @@
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use nexora_rs::storage_engine::wal::wal_path;
+
+pub mod log;
+
+/// A database path unique to the test, cleared when created and removed, log included,
+/// when dropped.
+#[derive(Debug)]
+pub struct TempPath {
+    path: PathBuf,
+}
+
+impl TempPath {
+    /// `name` only has to be unique within the test file.
+    pub fn new(name: &str) -> Self {
+        let file_name = format!("nexora-{}-{name}-{}.nexora", env!("CARGO_CRATE_NAME"), std::process::id());
+        let path = std::env::temp_dir().join(file_name);
+        remove(&path);
+        Self { path }
+    }
+}
+
+fn remove(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    let _ = std::fs::remove_file(wal_path(path));
+}
+
+impl Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        remove(&self.path);
+    }
+}
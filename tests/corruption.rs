@@ -0,0 +1,170 @@
+use std::fs::OpenOptions;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+use nexora_rs::models::file_layout::{
+    stamp_page_checksum, IndexNode, Name, NexoraFile, NexoraHeader, Node, NodeSchema, OffsetTableChunk, PropertyType,
+    Section, INDEX_NODE_CAPACITY, KB1, OFFSET_ITEMS_PER_CHUNK, PAGE_SIZE,
+};
+use nexora_rs::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+/// Creates a fresh database file at a path unique to the test and returns its layout.
+async fn fresh_file(name: &str) -> (TempPath, NexoraFile) {
+    let path = TempPath::new(name);
+    let engine = StorageEngine::create(&path).await.unwrap();
+    (path, engine.file_layout)
+}
+
+/// Rewrites the page at `offset`, restamping its checksum when `stamp` is set.
+fn patch_page(path: &Path, offset: u64, stamp: bool, patch: impl FnOnce(&mut [u8; PAGE_SIZE])) {
+    let file = OpenOptions::new().read(true).write(true).open(path).unwrap();
+    let mut page = [0u8; PAGE_SIZE];
+    file.read_exact_at(&mut page, offset).unwrap();
+    patch(&mut page);
+    if stamp {
+        stamp_page_checksum(&mut page);
+    }
+    file.write_all_at(&page, offset).unwrap();
+}
+
+async fn load_error(path: &Path) -> CorruptedFileError {
+    match StorageEngine::load(path).await {
+        Err(StorageError::Corrupted(err)) => err,
+        other => panic!("expected a corrupted file error, got {other:?}"),
+    }
+}
+
+#[test]
+fn truncated_buffers_are_rejected() {
+    let page = NexoraFile::default().header.serialize();
+    assert!(matches!(NexoraHeader::deserialize(&page[..PAGE_SIZE - 1]), Err(CorruptedFileError::Truncated)));
+    assert!(matches!(OffsetTableChunk::deserialize(&[0u8; 100]), Err(CorruptedFileError::Truncated)));
+    assert!(matches!(Node::deserialize(&[0u8; KB1 - 8]), Err(CorruptedFileError::Truncated)));
+    let index_node = IndexNode::leaf(&[], &[], &[], u64::MAX).serialize();
+    assert!(matches!(IndexNode::deserialize(&index_node[..16]), Err(CorruptedFileError::Truncated)));
+}
+
+#[test]
+fn out_of_range_counts_are_rejected() {
+    let mut chunk = OffsetTableChunk::default().serialize();
+    chunk[0] = OFFSET_ITEMS_PER_CHUNK as u8 + 1;
+    assert!(matches!(
+        OffsetTableChunk::deserialize(&chunk),
+        Err(CorruptedFileError::TooManyOffsetItems(255))
+    ));
+
+    let mut name = Name::default().serialize();
+    name[8] = 56;
+    assert!(matches!(Name::deserialize(&name), Err(CorruptedFileError::InvalidNameLength(56))));
+
+    let mut schema = NodeSchema::default().serialize();
+    schema[8..10].copy_from_slice(&121u16.to_le_bytes());
+    assert!(matches!(NodeSchema::deserialize(&schema), Err(CorruptedFileError::InvalidPropertyCount(121))));
+
+    let mut index_node = IndexNode::leaf(&[], &[], &[], u64::MAX).serialize();
+    index_node[6..8].copy_from_slice(&(INDEX_NODE_CAPACITY as u16 + 1).to_le_bytes());
+    assert!(matches!(IndexNode::deserialize(&index_node), Err(CorruptedFileError::TooManyIndexKeys(_))));
+}
+
+#[test]
+fn unknown_tags_are_rejected() {
+    assert!(matches!(PropertyType::try_from(14), Err(CorruptedFileError::UnknownPropertyType(14))));
+
+    let mut index_node = IndexNode::leaf(&[], &[], &[], u64::MAX).serialize();
+    index_node[0] = 3;
+    assert!(matches!(IndexNode::deserialize(&index_node), Err(CorruptedFileError::UnknownIndexNodeKind(3))));
+
+    // The last bit of the bitmap lies past the 120 property slots.
+    let mut node = Node::default().serialize();
+    node[KB1 - 32 - 1] = 0x80;
+    assert!(matches!(Node::deserialize(&node), Err(CorruptedFileError::InvalidPropertyBitmap)));
+
+    let mut header = NexoraFile::default().header.serialize();
+    header[16] = b'X';
+    assert!(matches!(NexoraHeader::deserialize(&header), Err(CorruptedFileError::InvalidMagicValue)));
+}
+
+#[tokio::test]
+async fn bit_flips_fail_the_checksum() {
+    let (path, layout) = fresh_file("bit-flip").await;
+    let offset = layout.footer.table(Section::Nodes).base_chunk_offset;
+    patch_page(&path, offset, false, |page| page[100] ^= 0x04);
+
+    assert!(matches!(
+        load_error(&path).await,
+        CorruptedFileError::ChecksumMismatch { offset: found } if found == offset
+    ));
+}
+
+#[tokio::test]
+async fn self_linked_chunks_are_rejected() {
+    let (path, layout) = fresh_file("self-linked").await;
+    let offset = layout.footer.table(Section::Edges).base_chunk_offset;
+    patch_page(&path, offset, true, |page| page[16..24].copy_from_slice(&offset.to_le_bytes()));
+
+    assert!(matches!(
+        load_error(&path).await,
+        CorruptedFileError::SelfReferencingChunk(found) if found == offset
+    ));
+}
+
+#[tokio::test]
+async fn broken_chunk_chains_are_rejected() {
+    let (path, layout) = fresh_file("broken-chain").await;
+    let offset = layout.footer.table(Section::Edges).base_chunk_offset;
+    let other = layout.footer.table(Section::Nodes).base_chunk_offset;
+    patch_page(&path, offset, true, |page| page[8..16].copy_from_slice(&other.to_le_bytes()));
+
+    assert!(matches!(
+        load_error(&path).await,
+        CorruptedFileError::BrokenChunkChain(found) if found == offset
+    ));
+}
+
+#[tokio::test]
+async fn oversized_chunks_are_rejected_on_load() {
+    let (path, layout) = fresh_file("oversized-chunk").await;
+    let offset = layout.footer.table(Section::NameTable).base_chunk_offset;
+    patch_page(&path, offset, true, |page| page[0] = 255);
+
+    assert!(matches!(load_error(&path).await, CorruptedFileError::TooManyOffsetItems(255)));
+}
+
+#[tokio::test]
+async fn truncated_files_are_rejected() {
+    let (path, layout) = fresh_file("truncated").await;
+    let file = OpenOptions::new().write(true).open(&path).unwrap();
+    file.set_len(layout.footer.table(Section::Nodes).base_chunk_offset).unwrap();
+
+    assert!(matches!(load_error(&path).await, CorruptedFileError::OffsetBeyondFileLength { .. }));
+}
+
+#[test]
+fn legacy_footers_at_the_end_of_the_address_space_are_rejected() {
+    let mut header = NexoraFile::default().header;
+    header.version = 0;
+    header.footer_offset = u64::MAX - (PAGE_SIZE as u64 - 1);
+    assert!(matches!(
+        NexoraHeader::deserialize(&header.serialize()),
+        Err(CorruptedFileError::InvalidOffsetValue)
+    ));
+}
+
+#[tokio::test]
+async fn oversized_page_counts_are_rejected() {
+    for (name, free, reserved) in [("reserved-pages", 0, u64::MAX / PAGE_SIZE as u64), ("free-pages", u64::MAX, 1)] {
+        let (path, layout) = fresh_file(name).await;
+        let mut footer = layout.footer;
+        footer.nb_free_pages = free;
+        footer.nb_reserved_pages = reserved;
+        patch_page(&path, layout.header.footer_offset, true, |page| *page = footer.serialize());
+
+        assert!(matches!(
+            load_error(&path).await,
+            CorruptedFileError::InvalidPageCounts { free: f, reserved: r } if f == free && r == reserved
+        ));
+    }
+}
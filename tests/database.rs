@@ -0,0 +1,174 @@
+
+use nexora_rs::models::file_layout::{Node, PropertyType};
+use nexora_rs::models::property_value::PropertyValue;
+use nexora_rs::models::schema_builder::builder::{NodeSchemaBuilder, PropertyBuilder};
+use nexora_rs::storage_engine::database::Database;
+use nexora_rs::storage_engine::engine::StorageError;
+
+mod common;
+use common::TempPath;
+
+const WRITERS: u64 = 4;
+const READERS: u64 = 16;
+const BATCHES: u64 = 10;
+const BATCH_SIZE: u64 = 5;
+
+/// A fresh database at a path unique to the test, with a node schema holding an `Int64` weight.
+async fn fresh_database(name: &str) -> (TempPath, Database) {
+    let path = TempPath::new(name);
+    let database = Database::create(&path).await.unwrap();
+    let weight = PropertyBuilder::new("weight".to_string(), PropertyType::Int64, true);
+    let schema = NodeSchemaBuilder::new(1).property(weight);
+    database.write().await.register_node_schema(&schema).await.unwrap();
+    (path, database)
+}
+
+/// Node ids written by `writer`, batch after batch.
+fn node_ids(writer: u64, batch: u64) -> impl Iterator<Item = u64> {
+    let first = (writer * BATCHES + batch) * BATCH_SIZE + 1;
+    first..first + BATCH_SIZE
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn readers_only_see_whole_transactions() {
+    let (path, database) = fresh_database("whole-transactions").await;
+
+    let mut writers = Vec::new();
+    for writer in 0..WRITERS {
+        let database = database.clone();
+        writers.push(tokio::spawn(async move {
+            for batch in 0..BATCHES {
+                database
+                    .transaction(async |tx| {
+                        for id in node_ids(writer, batch) {
+                            let mut node = Node { id, schema_id: 1, ..Default::default() };
+                            node.set(tx, "weight", PropertyValue::Int64(id as i64 * 10)).await?;
+                            tx.insert_node(&node).await?;
+                        }
+                        Ok(())
+                    })
+                    .await
+                    .unwrap();
+            }
+        }));
+    }
+
+    let mut readers = Vec::new();
+    for reader in 0..READERS {
+        let database = database.clone();
+        readers.push(tokio::spawn(async move {
+            let mut seen = 0;
+            while seen < WRITERS * BATCHES * BATCH_SIZE {
+                let engine = database.read();
+                let nodes = engine.scan_nodes(..).await.unwrap();
+                assert_eq!(nodes.len() as u64 % BATCH_SIZE, 0, "reader {reader} saw a partial transaction");
+                assert!(nodes.len() as u64 >= seen, "reader {reader} went back in time");
+                for node in nodes.iter().filter(|node| node.id % 7 == reader % 7) {
+                    let weight = node.get(&engine, "weight").await.unwrap();
+                    assert_eq!(weight, PropertyValue::Int64(node.id as i64 * 10));
+                }
+                seen = nodes.len() as u64;
+                drop(engine);
+                tokio::task::yield_now().await;
+            }
+        }));
+    }
+
+    for task in writers.into_iter().chain(readers) {
+        task.await.unwrap();
+    }
+
+    drop(database);
+    let database = Database::load(&path).await.unwrap();
+    let engine = database.read();
+    for writer in 0..WRITERS {
+        for batch in 0..BATCHES {
+            for id in node_ids(writer, batch) {
+                assert!(engine.get_node(id).await.unwrap().is_some(), "node {id} was lost");
+            }
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn failed_transactions_leave_nothing_behind() {
+    let (_, database) = fresh_database("failed-transactions").await;
+
+    let mut tasks = Vec::new();
+    for writer in 0..WRITERS {
+        let database = database.clone();
+        tasks.push(tokio::spawn(async move {
+            for batch in 0..BATCHES {
+                let result = database
+                    .transaction(async |tx| {
+                        for id in node_ids(writer, batch) {
+                            tx.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await?;
+                        }
+                        // Every other batch inserts a duplicate and has to roll back.
+                        let last = node_ids(writer, batch).last().unwrap();
+                        if batch % 2 == 1 {
+                            tx.insert_node(&Node { id: last, schema_id: 1, ..Default::default() }).await?;
+                        }
+                        Ok(last)
+                    })
+                    .await;
+
+                match batch % 2 {
+                    0 => assert_eq!(result.unwrap(), node_ids(writer, batch).last().unwrap()),
+                    _ => assert!(matches!(result, Err(StorageError::AlreadyExists(_)))),
+                }
+            }
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let engine = database.read();
+    let nodes = engine.scan_nodes(..).await.unwrap();
+    assert_eq!(nodes.len() as u64, WRITERS * BATCHES / 2 * BATCH_SIZE);
+    assert!(nodes.iter().all(|node| ((node.id - 1) / BATCH_SIZE % BATCHES).is_multiple_of(2)));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn readers_do_not_wait_for_an_open_transaction() {
+    let (_, database) = fresh_database("open-transaction").await;
+    database
+        .transaction(async |tx| {
+            for id in node_ids(0, 0) {
+                tx.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await?;
+            }
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    let mut engine = database.write().await;
+    let mut transaction = engine.begin().await.unwrap();
+    for id in node_ids(0, 0) {
+        transaction.delete_node(id).await.unwrap();
+    }
+
+    // The writer holds the engine, yet new readers start and finish meanwhile.
+    let mut readers = Vec::new();
+    for _ in 0..READERS {
+        let database = database.clone();
+        readers.push(tokio::spawn(async move {
+            let snapshot = database.read();
+            for id in node_ids(0, 0) {
+                assert!(snapshot.get_node(id).await.unwrap().is_some());
+            }
+            snapshot.scan_nodes(..).await.unwrap().len()
+        }));
+    }
+    for reader in readers {
+        assert_eq!(reader.await.unwrap() as u64, BATCH_SIZE);
+    }
+
+    let before = database.read();
+    transaction.commit().await.unwrap();
+    assert_eq!(database.read().scan_nodes(..).await.unwrap().len() as u64, BATCH_SIZE);
+    drop(engine);
+    assert!(database.read().scan_nodes(..).await.unwrap().is_empty());
+    assert_eq!(before.scan_nodes(..).await.unwrap().len() as u64, BATCH_SIZE);
+}
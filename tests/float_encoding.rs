@@ -0,0 +1,91 @@
+use nexora_rs::models::file_layout::PropertyType;
+use nexora_rs::models::property_value::PropertyValue;
+use nexora_rs::utils::encoding::float::float::{F16, F8E4M3};
+
+#[test]
+fn f16_round_trips_every_bit_pattern() {
+    for bits in 0..=u16::MAX {
+        let value = F16::from_bits(bits);
+        let back = F16::from_f32(value.to_f32());
+        if value.is_nan() {
+            assert!(back.is_nan(), "{bits:#06x} lost its NaN");
+        } else {
+            assert_eq!(back.to_bits(), bits, "{bits:#06x} did not round-trip");
+        }
+    }
+}
+
+#[test]
+fn f16_rounds_to_nearest_even() {
+    assert_eq!(F16::from_f32(1.0).to_bits(), 0x3c00);
+    assert_eq!(F16::from_f32(-2.0).to_bits(), 0xc000);
+    assert_eq!(F16::from_f32(65504.0).to_bits(), 0x7bff);
+
+    // Halfway between 1.0 and the next value rounds down to the even mantissa...
+    assert_eq!(F16::from_f32(1.0 + 2f32.powi(-11)).to_bits(), 0x3c00);
+    // ...and halfway above an odd mantissa rounds up.
+    assert_eq!(F16::from_f32(1.0 + 3.0 * 2f32.powi(-11)).to_bits(), 0x3c02);
+
+    // 65520 is halfway to the next (out of range) value and rounds to infinity.
+    assert_eq!(F16::from_f32(65519.0).to_bits(), 0x7bff);
+    assert_eq!(F16::from_f32(65520.0).to_bits(), 0x7c00);
+    assert_eq!(F16::from_f32(f32::NEG_INFINITY).to_bits(), 0xfc00);
+    assert!(F16::from_f32(f32::NAN).is_nan());
+}
+
+#[test]
+fn f16_handles_subnormals_and_underflow() {
+    assert_eq!(F16::from_f32(2f32.powi(-24)).to_bits(), 0x0001);
+    assert_eq!(F16::from_f32(2f32.powi(-14) - 2f32.powi(-24)).to_bits(), 0x03ff);
+    assert_eq!(F16::from_f32(2f32.powi(-25)).to_bits(), 0x0000);
+    assert_eq!(F16::from_f32(1.5 * 2f32.powi(-25)).to_bits(), 0x0001);
+    assert_eq!(F16::from_f32(-1e-30).to_bits(), 0x8000);
+    assert_eq!(F16::from_bits(0x0001).to_f32(), 2f32.powi(-24));
+}
+
+#[test]
+fn f8_round_trips_every_bit_pattern() {
+    for bits in 0..=u8::MAX {
+        let value = F8E4M3::from_bits(bits);
+        let back = F8E4M3::from_f32(value.to_f32());
+        if value.is_nan() {
+            assert!(back.is_nan(), "{bits:#04x} lost its NaN");
+        } else {
+            assert_eq!(back.to_bits(), bits, "{bits:#04x} did not round-trip");
+        }
+    }
+}
+
+#[test]
+fn f8_rounds_and_saturates() {
+    assert_eq!(F8E4M3::from_f32(1.0).to_bits(), 0x38);
+    assert_eq!(F8E4M3::from_f32(448.0).to_bits(), 0x7e);
+    assert_eq!(F8E4M3::from_f32(-448.0).to_bits(), 0xfe);
+    assert_eq!(F8E4M3::from_f32(1000.0).to_bits(), 0x7e);
+    assert_eq!(F8E4M3::from_f32(f32::INFINITY).to_bits(), 0x7e);
+    assert!(F8E4M3::from_f32(f32::NAN).is_nan());
+
+    // 1.0625 is halfway between 1.0 and 1.125 and rounds to the even mantissa.
+    assert_eq!(F8E4M3::from_f32(1.0625).to_bits(), 0x38);
+    assert_eq!(F8E4M3::from_f32(1.1875).to_bits(), 0x3a);
+
+    assert_eq!(F8E4M3::from_f32(2f32.powi(-9)).to_bits(), 0x01);
+    assert_eq!(F8E4M3::from_f32(2f32.powi(-10)).to_bits(), 0x00);
+    assert_eq!(F8E4M3::from_f32(0.75 * 2f32.powi(-9)).to_bits(), 0x01);
+    assert_eq!(F8E4M3::from_bits(0x08).to_f32(), 2f32.powi(-6));
+}
+
+#[test]
+fn low_precision_property_values_round_trip_through_their_slot() {
+    let half = PropertyValue::Float16(F16::from_f32(0.333));
+    let raw = half.to_raw(PropertyType::Float16).unwrap();
+    assert!(raw <= u16::MAX as u64);
+    assert_eq!(PropertyValue::from_raw(PropertyType::Float16, raw), Some(half));
+
+    let quarter = PropertyValue::Float8(F8E4M3::from_f32(-3.5));
+    let raw = quarter.to_raw(PropertyType::Float8).unwrap();
+    assert!(raw <= u8::MAX as u64);
+    assert_eq!(PropertyValue::from_raw(PropertyType::Float8, raw), Some(quarter.clone()));
+
+    assert_eq!(quarter.to_raw(PropertyType::Float16), None);
+}
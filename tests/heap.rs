@@ -0,0 +1,152 @@
+use nexora_rs::models::file_layout::{Node, PropertyType};
+use nexora_rs::models::property_value::PropertyValue;
+use nexora_rs::models::schema_builder::builder::{NodeSchemaBuilder, PropertyBuilder};
+use nexora_rs::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+/// A fresh engine at a path unique to the test, with a node schema holding one property of
+/// each heap class but `String64`.
+async fn fresh_engine(name: &str) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let schema = NodeSchemaBuilder::new(1)
+        .property(PropertyBuilder::new("name".to_string(), PropertyType::String32, true))
+        .property(PropertyBuilder::new("bio".to_string(), PropertyType::String512, true))
+        .property(PropertyBuilder::new("blob".to_string(), PropertyType::Page, true));
+    engine.register_node_schema(&schema).await.unwrap();
+    (path, engine)
+}
+
+async fn insert_named(engine: &mut StorageEngine, id: u64, name: &str) -> Node {
+    let mut node = Node { id, schema_id: 1, ..Default::default() };
+    node.set(engine, "name", PropertyValue::String(name.to_string())).await.unwrap();
+    engine.insert_node(&node).await.unwrap();
+    node
+}
+
+#[tokio::test]
+async fn values_of_every_class_round_trip() {
+    let (path, mut engine) = fresh_engine("round-trip").await;
+    let bio = "b".repeat(400);
+    let blob: Vec<u8> = (0..3000).map(|byte| byte as u8).collect();
+
+    let mut node = Node { id: 1, schema_id: 1, ..Default::default() };
+    node.set(&mut engine, "name", PropertyValue::String("ada".to_string())).await.unwrap();
+    node.set(&mut engine, "bio", PropertyValue::String(bio.clone())).await.unwrap();
+    node.set(&mut engine, "blob", PropertyValue::Bytes(blob.clone())).await.unwrap();
+    engine.insert_node(&node).await.unwrap();
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    let node = engine.get_node(1).await.unwrap().unwrap();
+    assert_eq!(node.get(&engine, "name").await.unwrap(), PropertyValue::String("ada".to_string()));
+    assert_eq!(node.get(&engine, "bio").await.unwrap(), PropertyValue::String(bio.clone()));
+    assert_eq!(node.get(&engine, "blob").await.unwrap(), PropertyValue::Bytes(blob.clone()));
+    assert_eq!(engine.read_node_string(&node, 1).await.unwrap(), Some(bio));
+    assert_eq!(engine.read_node_bytes(&node, 2).await.unwrap(), Some(blob));
+}
+
+#[tokio::test]
+async fn oversized_values_are_refused() {
+    let (_, mut engine) = fresh_engine("oversized").await;
+    let mut node = Node { id: 1, schema_id: 1, ..Default::default() };
+
+    let result = node.set(&mut engine, "name", PropertyValue::String("n".repeat(33))).await;
+    assert!(matches!(
+        result,
+        Err(StorageError::ValueTooLarge { property_type: PropertyType::String32, len: 33 })
+    ));
+    assert!(!node.is_property_present(0));
+}
+
+#[tokio::test]
+async fn updates_free_the_replaced_cell_for_reuse() {
+    let (_, mut engine) = fresh_engine("update").await;
+    let mut node = insert_named(&mut engine, 1, "first").await;
+    let first = node.property_values[0];
+
+    node.set(&mut engine, "name", PropertyValue::String("second".to_string())).await.unwrap();
+    let second = node.property_values[0];
+    assert_ne!(second, first);
+    engine.update_node(&node).await.unwrap();
+    assert_eq!(engine.file_layout.footer.heap_free_lists[0], first);
+
+    let reused = insert_named(&mut engine, 2, "third").await;
+    assert_eq!(reused.property_values[0], first);
+    let node = engine.get_node(1).await.unwrap().unwrap();
+    assert_eq!(node.get(&engine, "name").await.unwrap(), PropertyValue::String("second".to_string()));
+}
+
+#[tokio::test]
+async fn deletes_free_every_cell_of_the_record() {
+    let (_, mut engine) = fresh_engine("delete").await;
+    let mut node = Node { id: 1, schema_id: 1, ..Default::default() };
+    node.set(&mut engine, "name", PropertyValue::String("ada".to_string())).await.unwrap();
+    node.set(&mut engine, "blob", PropertyValue::Bytes(vec![7; 100])).await.unwrap();
+    engine.insert_node(&node).await.unwrap();
+
+    engine.delete_node(1).await.unwrap();
+    let free_lists = engine.file_layout.footer.heap_free_lists;
+    assert_eq!(free_lists[0], node.property_values[0]);
+    assert_eq!(free_lists[3], node.property_values[2]);
+    assert_eq!(engine.write_heap_value(PropertyType::Page, b"again").await.unwrap(), node.property_values[2]);
+}
+
+#[tokio::test]
+async fn cells_cannot_be_freed_twice() {
+    let (_, mut engine) = fresh_engine("double-free").await;
+    let offset = engine.write_heap_value(PropertyType::String32, b"once").await.unwrap();
+    engine.free_heap_value(PropertyType::String32, offset).await.unwrap();
+    let free_lists = engine.file_layout.footer.heap_free_lists;
+
+    assert!(matches!(
+        engine.free_heap_value(PropertyType::String32, offset).await,
+        Err(StorageError::Corrupted(CorruptedFileError::InvalidOffsetValue))
+    ));
+    assert_eq!(engine.file_layout.footer.heap_free_lists, free_lists);
+
+    // The list still hands the cell out a single time.
+    let first = engine.write_heap_value(PropertyType::String32, b"a").await.unwrap();
+    let second = engine.write_heap_value(PropertyType::String32, b"b").await.unwrap();
+    assert_eq!(first, offset);
+    assert_ne!(second, offset);
+}
+
+#[tokio::test]
+async fn records_only_reference_allocated_cells_of_their_class() {
+    let (_, mut engine) = fresh_engine("cell-checks").await;
+    let freed = engine.write_heap_value(PropertyType::String32, b"gone").await.unwrap();
+    engine.free_heap_value(PropertyType::String32, freed).await.unwrap();
+    let other_class = engine.write_heap_value(PropertyType::String512, b"long").await.unwrap();
+
+    for (id, offset) in [(1, freed), (2, other_class)] {
+        let mut node = Node { id, schema_id: 1, ..Default::default() };
+        node.set_property(0, offset);
+        assert!(matches!(
+            engine.insert_node(&node).await,
+            Err(StorageError::SchemaViolation { property, .. }) if property == "name"
+        ));
+    }
+    assert!(engine.get_node(1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn invalid_utf8_strings_fail_to_read() {
+    let (_, mut engine) = fresh_engine("utf8").await;
+    let offset = engine.write_heap_value(PropertyType::String32, &[0x61, 0xff, 0xfe]).await.unwrap();
+    let mut node = Node { id: 1, schema_id: 1, ..Default::default() };
+    node.set_property(0, offset);
+    engine.insert_node(&node).await.unwrap();
+
+    assert!(matches!(
+        node.get(&engine, "name").await,
+        Err(StorageError::Corrupted(CorruptedFileError::InvalidUtf8String))
+    ));
+    assert!(matches!(
+        engine.read_node_string(&node, 0).await,
+        Err(StorageError::Corrupted(CorruptedFileError::InvalidUtf8String))
+    ));
+    assert_eq!(engine.read_node_bytes(&node, 0).await.unwrap(), Some(vec![0x61, 0xff, 0xfe]));
+}
@@ -0,0 +1,89 @@
+use nexora_rs::models::file_layout::{Edge, Node, Section, OFFSET_ITEMS_PER_CHUNK};
+use nexora_rs::models::schema_builder::builder::{EdgeSchemaBuilder, NodeSchemaBuilder};
+use nexora_rs::storage_engine::engine::StorageEngine;
+
+mod common;
+use common::TempPath;
+
+/// Enough nodes to span two chunks of the node table.
+const NODES: u64 = OFFSET_ITEMS_PER_CHUNK as u64 + 46;
+
+async fn open_with_id_map(path: &TempPath) -> StorageEngine {
+    StorageEngine::options().create(true).id_map(true).open(path).await.unwrap()
+}
+
+/// Asserts that the id map finds exactly the nodes and edges the index holds, as `nodes` and
+/// `edges`, and no record under any other id.
+async fn assert_matches_index(engine: &StorageEngine, nodes: &[u64], edges: &[u64]) {
+    let indexed: Vec<u64> = engine.scan_index(Section::Nodes, ..).await.unwrap().iter().map(|item| item.id).collect();
+    assert_eq!(indexed, nodes);
+    let indexed: Vec<u64> = engine.scan_index(Section::Edges, ..).await.unwrap().iter().map(|item| item.id).collect();
+    assert_eq!(indexed, edges);
+
+    for id in 0..=NODES + 1 {
+        let found = engine.get_node(id).await.unwrap().map(|node| node.id);
+        assert_eq!(found, nodes.contains(&id).then_some(id), "node {id}");
+        let found = engine.get_edge(id).await.unwrap().map(|edge| edge.id);
+        assert_eq!(found, edges.contains(&id).then_some(id), "edge {id}");
+    }
+    let stats = engine.id_map_stats().unwrap();
+    assert_eq!((stats.nodes, stats.edges), (nodes.len(), edges.len()));
+}
+
+#[tokio::test]
+async fn lookups_match_the_index_through_inserts_and_deletes() {
+    let path = TempPath::new("lookups");
+    let mut engine = open_with_id_map(&path).await;
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    engine.register_edge_schema(&EdgeSchemaBuilder::new(1)).await.unwrap();
+    assert_matches_index(&engine, &[], &[]).await;
+
+    for id in 1..=NODES {
+        engine.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    for id in 1..=10 {
+        let edge = Edge { id, schema_id: 1, source_id: id, destination_id: id + 1, ..Default::default() };
+        engine.insert_edge(&edge).await.unwrap();
+    }
+    let mut nodes: Vec<u64> = (1..=NODES).collect();
+    let mut edges: Vec<u64> = (1..=10).collect();
+    assert_matches_index(&engine, &nodes, &edges).await;
+
+    for id in (1..=20).chain([NODES]) {
+        engine.delete_node(id).await.unwrap();
+    }
+    engine.delete_edge(1).await.unwrap();
+    engine.delete_edge(10).await.unwrap();
+    nodes.retain(|id| !(1..=20).contains(id) && *id != NODES);
+    edges.retain(|id| ![1, 10].contains(id));
+    assert_matches_index(&engine, &nodes, &edges).await;
+
+    // Records inserted again land in the slots the deletes freed.
+    for id in 1..=5 {
+        engine.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    nodes.splice(0..0, 1..=5);
+    assert_matches_index(&engine, &nodes, &edges).await;
+
+    // Changes of a rolled back transaction leave the map as well.
+    let mut transaction = engine.begin().await.unwrap();
+    transaction.delete_node(21).await.unwrap();
+    transaction.insert_node(&Node { id: 6, schema_id: 1, ..Default::default() }).await.unwrap();
+    transaction.rollback();
+    assert_matches_index(&engine, &nodes, &edges).await;
+    drop(engine);
+
+    let engine = open_with_id_map(&path).await;
+    assert_matches_index(&engine, &nodes, &edges).await;
+}
+
+#[tokio::test]
+async fn engines_without_an_id_map_report_none() {
+    let path = TempPath::new("disabled");
+    let engine = StorageEngine::create(&path).await.unwrap();
+    assert!(engine.id_map_stats().is_none());
+    drop(engine);
+
+    let engine = open_with_id_map(&path).await;
+    assert_eq!(engine.id_map_stats().map(|stats| (stats.nodes, stats.edges)), Some((0, 0)));
+}
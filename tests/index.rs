@@ -0,0 +1,161 @@
+use std::fs::OpenOptions;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+use nexora_rs::models::file_layout::{
+    split_offset_item_position, stamp_page_checksum, IndexNode, Node, Section, INDEX_NODE_CAPACITY, INVALID_OFFSET,
+    PAGE_SIZE,
+};
+use nexora_rs::models::schema_builder::builder::NodeSchemaBuilder;
+use nexora_rs::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+/// A fresh engine at a path unique to the test, holding a node for every id of `ids`.
+async fn engine_with(name: &str, ids: impl Iterator<Item = u64>) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    for id in ids {
+        engine.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    (path, engine)
+}
+
+async fn index_root(engine: &StorageEngine) -> u64 {
+    let (_, _, item) = engine.find_offset_item(Section::Indices, Section::Nodes as u64).await.unwrap().unwrap();
+    item.offset
+}
+
+/// The index node stored at `offset`, as written to the file.
+fn read_index_node(path: &Path, offset: u64) -> IndexNode {
+    let file = std::fs::File::open(path).unwrap();
+    let mut page = [0u8; PAGE_SIZE];
+    file.read_exact_at(&mut page, offset).unwrap();
+    IndexNode::deserialize(&page).unwrap()
+}
+
+/// Every leaf of the tree rooted at `root`, in id order.
+fn leaves(path: &Path, root: u64) -> Vec<IndexNode> {
+    let mut node = read_index_node(path, root);
+    while !node.is_leaf() {
+        node = read_index_node(path, node.values()[0]);
+    }
+
+    let mut leaves = vec![node];
+    while node.next_leaf != INVALID_OFFSET {
+        node = read_index_node(path, node.next_leaf);
+        leaves.push(node);
+    }
+    leaves
+}
+
+async fn node_ids(engine: &StorageEngine, range: impl std::ops::RangeBounds<u64>) -> Vec<u64> {
+    engine.scan_nodes(range).await.unwrap().iter().map(|node| node.id).collect()
+}
+
+#[tokio::test]
+async fn overfull_leaves_split_under_a_new_root() {
+    let count = INDEX_NODE_CAPACITY as u64 + 1;
+    let (path, engine) = engine_with("split", 1..=count).await;
+
+    let root = read_index_node(&path, index_root(&engine).await);
+    assert!(!root.is_leaf());
+    assert_eq!(root.values().len(), 2);
+
+    let leaves = leaves(&path, index_root(&engine).await);
+    assert_eq!(leaves.len(), 2);
+    assert_eq!(leaves[1].keys()[0], root.keys()[0]);
+    let keys: Vec<u64> = leaves.iter().flat_map(|leaf| leaf.keys().to_vec()).collect();
+    assert_eq!(keys, (1..=count).collect::<Vec<_>>());
+    assert_eq!(node_ids(&engine, ..).await, keys);
+}
+
+#[tokio::test]
+async fn short_leaves_merge_and_collapse_the_root() {
+    let count = INDEX_NODE_CAPACITY as u64 + 1;
+    let (path, mut engine) = engine_with("merge", 1..=count).await;
+    let free_pages = engine.page_stats().free_pages;
+
+    // The right leaf runs short first and is folded back into the left one.
+    for id in (count / 2..=count).rev() {
+        engine.delete_node(id).await.unwrap();
+    }
+
+    let root = read_index_node(&path, index_root(&engine).await);
+    assert!(root.is_leaf());
+    assert_eq!(root.keys(), (1..count / 2).collect::<Vec<_>>());
+    assert_eq!(root.next_leaf, INVALID_OFFSET);
+    assert!(engine.page_stats().free_pages >= free_pages + 2);
+    assert_eq!(node_ids(&engine, ..).await, (1..count / 2).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn scans_honour_every_bound() {
+    // Spread over several leaves, with gaps between ids.
+    let ids: Vec<u64> = (1..=2 * INDEX_NODE_CAPACITY as u64).map(|id| id * 3).collect();
+    let (_, engine) = engine_with("ranges", ids.iter().copied()).await;
+    let expect = |range: &dyn Fn(u64) -> bool| ids.iter().copied().filter(|&id| range(id)).collect::<Vec<_>>();
+
+    assert_eq!(node_ids(&engine, ..).await, ids);
+    assert_eq!(node_ids(&engine, 300..=600).await, expect(&|id| (300..=600).contains(&id)));
+    assert_eq!(node_ids(&engine, 301..600).await, expect(&|id| (301..600).contains(&id)));
+    assert_eq!(node_ids(&engine, ..=9).await, [3, 6, 9]);
+    assert_eq!(node_ids(&engine, ..9).await, [3, 6]);
+    assert_eq!(node_ids(&engine, 1000..).await, expect(&|id| id >= 1000));
+
+    use std::ops::Bound::{Excluded, Included};
+    assert_eq!(node_ids(&engine, (Excluded(3), Included(12))).await, [6, 9, 12]);
+    assert!(node_ids(&engine, (Excluded(u64::MAX), Included(u64::MAX))).await.is_empty());
+    assert!(node_ids(&engine, 4..6).await.is_empty());
+    assert!(node_ids(&engine, ids.last().unwrap() + 1..).await.is_empty());
+}
+
+#[tokio::test]
+async fn rebuilds_recover_a_damaged_tree() {
+    let (path, mut engine) = engine_with("rebuild", 1..=2 * INDEX_NODE_CAPACITY as u64).await;
+    let root = index_root(&engine).await;
+    assert!(matches!(engine.rebuild_index(Section::NameTable).await, Err(StorageError::NotIndexed(Section::NameTable))));
+    drop(engine);
+
+    let file = OpenOptions::new().write(true).open(&path).unwrap();
+    let mut page = [0xa5u8; PAGE_SIZE];
+    stamp_page_checksum(&mut page);
+    file.write_all_at(&page, root).unwrap();
+
+    let mut engine = StorageEngine::load(&path).await.unwrap();
+    assert!(matches!(
+        engine.scan_nodes(..).await,
+        Err(StorageError::Corrupted(CorruptedFileError::UnknownIndexNodeKind(0xa5)))
+    ));
+
+    engine.rebuild_index(Section::Nodes).await.unwrap();
+    assert_eq!(node_ids(&engine, ..).await, (1..=2 * INDEX_NODE_CAPACITY as u64).collect::<Vec<_>>());
+    assert!(engine.get_node(7).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn deletes_keep_offset_table_positions_in_step() {
+    let count = 3 * INDEX_NODE_CAPACITY as u64;
+    let (path, mut engine) = engine_with("positions", 1..=count).await;
+
+    // A scattered order moves items in from the tail chunk all over the tree.
+    let mut remaining: Vec<u64> = (1..=count).collect();
+    let order: Vec<u64> = (1..=count).map(|step| step * 7 % count + 1).collect();
+    for (step, id) in order.iter().enumerate() {
+        engine.delete_node(*id).await.unwrap();
+        remaining.retain(|other| other != id);
+
+        if step % 100 == 0 || remaining.is_empty() {
+            assert_eq!(node_ids(&engine, ..).await, remaining);
+            for leaf in leaves(&path, index_root(&engine).await) {
+                for (&key, &position) in leaf.keys().iter().zip(leaf.positions()) {
+                    let (chunk, index, _) = engine.find_offset_item(Section::Nodes, key).await.unwrap().unwrap();
+                    assert_eq!(split_offset_item_position(position), (chunk, index));
+                }
+            }
+        }
+    }
+    assert_eq!(engine.file_layout.footer.table(Section::Nodes).nb_total_items, 0);
+}
@@ -0,0 +1,120 @@
+use std::fs::OpenOptions;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+use nexora_rs::models::file_layout::{
+    format_version, stamp_page_checksum, Node, FILE_FORMAT_VERSION, HEADER_SLOT_OFFSETS, PAGE_SIZE,
+};
+use nexora_rs::models::schema_builder::builder::NodeSchemaBuilder;
+use nexora_rs::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+use nexora_rs::storage_engine::migration::Migration;
+use nexora_rs::storage_engine::wal::wal_path;
+
+mod common;
+use common::TempPath;
+
+/// Byte offset of `NexoraHeader::version` within a header page.
+const HEADER_VERSION_OFFSET: usize = 22;
+
+/// A copy of the 0.0 fixture, at a path unique to the test.
+fn legacy_file(name: &str) -> TempPath {
+    let path = TempPath::new(name);
+    std::fs::copy(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/legacy-0.0.nexora"), &path).unwrap();
+    path
+}
+
+/// Stamps `version` into both header slots of the file at `path`.
+fn set_header_version(path: &Path, version: u16) {
+    let file = OpenOptions::new().read(true).write(true).open(path).unwrap();
+    for slot_offset in HEADER_SLOT_OFFSETS {
+        let mut page = [0u8; PAGE_SIZE];
+        file.read_exact_at(&mut page, slot_offset).unwrap();
+        page[HEADER_VERSION_OFFSET..HEADER_VERSION_OFFSET + 2].copy_from_slice(&version.to_le_bytes());
+        stamp_page_checksum(&mut page);
+        file.write_all_at(&page, slot_offset).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn legacy_files_need_a_migration_to_be_written() {
+    let path = legacy_file("required");
+
+    assert!(matches!(
+        StorageEngine::load(&path).await,
+        Err(StorageError::MigrationRequired { found: 0, current: FILE_FORMAT_VERSION })
+    ));
+
+    let engine = StorageEngine::options().read_only(true).open(&path).await.unwrap();
+    assert!(engine.needs_migration());
+    assert!(engine.get_node(1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn dry_runs_report_every_step_without_writing() {
+    let path = legacy_file("dry-run");
+    let before = std::fs::read(&path).unwrap();
+
+    let mut engine = StorageEngine::options().read_only(true).open(&path).await.unwrap();
+    let report = engine.migrate(true).await.unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.from_version, format_version(0, 0));
+    assert_eq!(report.to_version, FILE_FORMAT_VERSION);
+    assert_eq!(report.steps.first(), Some(&Migration::PageChecksums));
+    assert!(report.steps.windows(2).all(|steps| steps[0].to_version() == steps[1].from_version()));
+    assert_eq!(report.steps.last().unwrap().to_version(), FILE_FORMAT_VERSION);
+    assert!(report.pages_rewritten > 0);
+
+    drop(engine);
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+}
+
+#[tokio::test]
+async fn legacy_files_migrate_to_the_current_version() {
+    let path = legacy_file("migrate");
+
+    let mut engine = StorageEngine::options().migrate(true).open(&path).await.unwrap();
+    assert_eq!(engine.file_layout.header.version, FILE_FORMAT_VERSION);
+    assert!(engine.file_layout.header.has_checksums());
+    assert!(engine.pending_migrations().is_empty());
+
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    engine.insert_node(&Node { id: 7, schema_id: 1, ..Default::default() }).await.unwrap();
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    assert_eq!(engine.file_layout.header.version, FILE_FORMAT_VERSION);
+    assert_eq!(engine.scan_nodes(..).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn newer_major_versions_are_refused() {
+    let path = TempPath::new("newer-major");
+    drop(StorageEngine::create(&path).await.unwrap());
+    set_header_version(&path, format_version(1, 0));
+
+    for read_only in [false, true] {
+        let result = StorageEngine::options().read_only(read_only).open(&path).await;
+        assert!(matches!(result, Err(StorageError::Corrupted(CorruptedFileError::UnsupportedVersion(0x100)))));
+    }
+}
+
+#[tokio::test]
+async fn newer_minor_versions_open_read_only() {
+    let path = TempPath::new("newer-minor");
+    drop(StorageEngine::create(&path).await.unwrap());
+    let newer = FILE_FORMAT_VERSION + 1;
+    set_header_version(&path, newer);
+
+    let mut engine = StorageEngine::load(&path).await.unwrap();
+    assert!(engine.read_only);
+    assert!(matches!(engine.register_node_schema(&NodeSchemaBuilder::new(1)).await, Err(StorageError::ReadOnly)));
+    drop(engine);
+
+    // The log of a newer build is neither replayed nor emptied.
+    let log = vec![0xa5u8; 3 * PAGE_SIZE];
+    std::fs::write(wal_path(&path), &log).unwrap();
+    let before = std::fs::read(&path).unwrap();
+    assert!(matches!(StorageEngine::load(&path).await, Err(StorageError::NewerLog { found }) if found == newer));
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+    assert_eq!(std::fs::read(wal_path(&path)).unwrap(), log);
+}
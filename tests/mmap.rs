@@ -0,0 +1,103 @@
+use std::fs::OpenOptions;
+
+use nexora_rs::models::file_layout::Node;
+use nexora_rs::models::schema_builder::builder::NodeSchemaBuilder;
+use nexora_rs::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+use nexora_rs::storage_engine::mmap::MmapStorageEngine;
+use nexora_rs::storage_engine::wal::wal_path;
+
+mod common;
+use common::log::{changed_pages, encode_batch};
+use common::TempPath;
+
+/// A fresh engine at a path unique to the test, holding nodes 1 to `count`.
+async fn engine_with_nodes(name: &str, count: u64) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    for id in 1..=count {
+        engine.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    (path, engine)
+}
+
+async fn node_ids(engine: &StorageEngine) -> Vec<u64> {
+    engine.scan_nodes(..).await.unwrap().iter().map(|node| node.id).collect()
+}
+
+#[tokio::test]
+async fn refreshes_map_the_grown_file() {
+    let (path, mut writer) = engine_with_nodes("growth", 5).await;
+    let mut mapped = MmapStorageEngine::open(&path).await.unwrap();
+    assert!(mapped.is_mapped());
+    let mapped_len = mapped.mapped_len();
+    assert_eq!(mapped_len, std::fs::metadata(&path).unwrap().len());
+
+    for id in 6..=200 {
+        writer.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    let file_len = std::fs::metadata(&path).unwrap().len();
+    assert!(file_len > mapped_len);
+
+    mapped.refresh().await.unwrap();
+    assert_eq!(mapped.mapped_len(), file_len);
+    assert_eq!(mapped.scan_nodes(..).await.unwrap().len(), 200);
+    assert_eq!(mapped.get_node(150).await.unwrap().unwrap().id, 150);
+}
+
+#[tokio::test]
+async fn failed_refreshes_keep_the_previous_layout() {
+    let (path, writer) = engine_with_nodes("truncation", 100).await;
+    let footer_offset = writer.file_layout.header.footer_offset;
+    drop(writer);
+    let mut mapped = MmapStorageEngine::open(&path).await.unwrap();
+    let mapped_len = mapped.mapped_len();
+    let layout = mapped.file_layout;
+    assert_eq!(mapped.get_node(90).await.unwrap().unwrap().id, 90);
+
+    // Cut the file right before the footer of the last commit.
+    OpenOptions::new().write(true).open(&path).unwrap().set_len(footer_offset).unwrap();
+
+    assert!(matches!(
+        mapped.refresh().await,
+        Err(StorageError::Corrupted(CorruptedFileError::OffsetBeyondFileLength { offset, file_len }))
+            if offset == footer_offset && file_len == footer_offset
+    ));
+    assert_eq!(mapped.mapped_len(), mapped_len);
+    assert_eq!(mapped.file_layout.header.generation, layout.header.generation);
+    assert_eq!(mapped.file_layout.footer.free_page_head, layout.footer.free_page_head);
+
+    // Pages past the new end fail to read rather than fault.
+    assert!(matches!(
+        mapped.get_node(90).await,
+        Err(StorageError::Corrupted(CorruptedFileError::OffsetBeyondFileLength { file_len, .. }))
+            if file_len == footer_offset
+    ));
+}
+
+#[tokio::test]
+async fn refreshes_drop_pages_recovered_from_an_emptied_log() {
+    let (path, mut writer) = engine_with_nodes("emptied-log", 5).await;
+    let before = std::fs::read(&path).unwrap();
+    let mut transaction = writer.begin().await.unwrap();
+    for id in 6..=8 {
+        transaction.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    transaction.commit().await.unwrap();
+    let after = std::fs::read(&path).unwrap();
+    writer.delete_node(8).await.unwrap();
+    drop(writer);
+    let latest = std::fs::read(&path).unwrap();
+
+    // Nodes 6 to 8 are still only in the log.
+    std::fs::write(&path, &before).unwrap();
+    std::fs::write(wal_path(&path), encode_batch(1, &changed_pages(&before, &after))).unwrap();
+    let mut mapped = MmapStorageEngine::open(&path).await.unwrap();
+    assert_eq!(node_ids(&mapped).await, (1..=8).collect::<Vec<_>>());
+
+    // A writer applied the log, emptied it and deleted node 8.
+    std::fs::write(&path, &latest).unwrap();
+    std::fs::write(wal_path(&path), []).unwrap();
+    mapped.refresh().await.unwrap();
+    assert_eq!(node_ids(&mapped).await, (1..=7).collect::<Vec<_>>());
+}
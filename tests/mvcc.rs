@@ -0,0 +1,93 @@
+use nexora_rs::models::file_layout::{Node, PropertyType};
+use nexora_rs::models::property_value::PropertyValue;
+use nexora_rs::models::schema_builder::builder::{NodeSchemaBuilder, PropertyBuilder};
+use nexora_rs::storage_engine::engine::{StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+/// A fresh engine at a path unique to the test, holding nodes 1 to 3 weighing ten times
+/// their id.
+async fn fresh_engine(name: &str) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let weight = PropertyBuilder::new("weight".to_string(), PropertyType::Int64, true);
+    engine.register_node_schema(&NodeSchemaBuilder::new(1).property(weight)).await.unwrap();
+    for id in 1..=3 {
+        set_weight(&mut engine, id, id as i64 * 10).await;
+    }
+    (path, engine)
+}
+
+/// Inserts node `id` with `weight`, or updates its weight if it exists.
+async fn set_weight(engine: &mut StorageEngine, id: u64, weight: i64) {
+    let existing = engine.get_node(id).await.unwrap();
+    let mut node = existing.unwrap_or(Node { id, schema_id: 1, ..Default::default() });
+    node.set(engine, "weight", PropertyValue::Int64(weight)).await.unwrap();
+    match existing {
+        Some(_) => engine.update_node(&node).await.unwrap(),
+        None => engine.insert_node(&node).await.unwrap(),
+    }
+}
+
+/// Weights of every node, in id order.
+async fn weights(engine: &StorageEngine) -> Vec<(u64, i64)> {
+    let mut weights = Vec::new();
+    for node in engine.scan_nodes(..).await.unwrap() {
+        let PropertyValue::Int64(weight) = node.get(engine, "weight").await.unwrap() else {
+            panic!("node {} has no weight", node.id);
+        };
+        weights.push((node.id, weight));
+    }
+    weights
+}
+
+#[tokio::test]
+async fn snapshots_keep_seeing_the_data_they_were_taken_at() {
+    let (_, mut engine) = fresh_engine("isolation").await;
+    let mut snapshot = engine.snapshot().await.unwrap();
+    let lsn = snapshot.lsn();
+
+    set_weight(&mut engine, 2, 99).await;
+    engine.delete_node(3).await.unwrap();
+    set_weight(&mut engine, 4, 40).await;
+
+    assert_eq!(weights(&engine).await, [(1, 10), (2, 99), (4, 40)]);
+    assert_eq!(weights(&snapshot).await, [(1, 10), (2, 20), (3, 30)]);
+    assert!(snapshot.get_node(4).await.unwrap().is_none());
+    assert_eq!(snapshot.lsn(), lsn);
+    assert!(matches!(snapshot.delete_node(1).await, Err(StorageError::ReadOnly)));
+
+    // A later snapshot sees the commits made since.
+    let later = engine.snapshot().await.unwrap();
+    assert!(later.lsn() > lsn);
+    assert_eq!(weights(&later).await, [(1, 10), (2, 99), (4, 40)]);
+}
+
+#[tokio::test]
+async fn page_versions_are_reclaimed_with_the_last_reader() {
+    let (_, mut engine) = fresh_engine("reclaim").await;
+    set_weight(&mut engine, 1, 11).await;
+    assert_eq!(engine.retained_page_versions(), 0);
+
+    let first = engine.snapshot().await.unwrap();
+    let second = engine.snapshot().await.unwrap();
+    set_weight(&mut engine, 1, 12).await;
+    let retained = engine.retained_page_versions();
+    assert!(retained > 0);
+
+    let later = engine.snapshot().await.unwrap();
+    set_weight(&mut engine, 2, 21).await;
+    assert!(engine.retained_page_versions() > retained);
+
+    // Versions stay as long as one snapshot at their lsn is open.
+    drop(first);
+    assert!(engine.retained_page_versions() > retained);
+    assert_eq!(weights(&second).await, [(1, 11), (2, 20), (3, 30)]);
+    drop(second);
+    assert!(engine.retained_page_versions() > 0);
+    assert_eq!(weights(&later).await, [(1, 12), (2, 20), (3, 30)]);
+    drop(later);
+    assert_eq!(engine.retained_page_versions(), 0);
+    assert_eq!(weights(&engine).await, [(1, 12), (2, 21), (3, 30)]);
+}
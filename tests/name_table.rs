@@ -0,0 +1,55 @@
+use nexora_rs::models::file_layout::{NAMES_PER_PAGE, PROPERTY_NAME_MAX_SIZE};
+use nexora_rs::storage_engine::engine::{StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+#[tokio::test]
+async fn interned_names_resolve_after_reopening() {
+    let path = TempPath::new("intern");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let alpha = engine.intern("alpha").await.unwrap();
+    let beta = engine.intern("beta").await.unwrap();
+    assert_ne!(alpha, beta);
+    assert_eq!(engine.intern("alpha").await.unwrap(), alpha);
+    assert_eq!(engine.name_table.len(), 2);
+    drop(engine);
+
+    let mut engine = StorageEngine::load(&path).await.unwrap();
+    assert_eq!(engine.resolve(alpha).unwrap(), "alpha");
+    assert_eq!(engine.resolve(beta).unwrap(), "beta");
+    assert_eq!(engine.name_table.lookup("beta"), Some(beta));
+    assert_eq!(engine.intern("beta").await.unwrap(), beta);
+    assert!(matches!(engine.resolve(beta + 1), Err(StorageError::NotFound(id)) if id == beta + 1));
+}
+
+#[tokio::test]
+async fn names_spill_over_several_pages() {
+    let path = TempPath::new("pages");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let names: Vec<String> = (0..NAMES_PER_PAGE * 2 + 3).map(|index| format!("name-{index}")).collect();
+    let mut ids = Vec::new();
+    for name in &names {
+        ids.push(engine.intern(name).await.unwrap());
+    }
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    assert_eq!(engine.name_table.len(), names.len());
+    for (name, id) in names.iter().zip(ids) {
+        assert_eq!(engine.resolve(id).unwrap(), *name);
+    }
+}
+
+#[tokio::test]
+async fn names_longer_than_the_maximum_are_refused() {
+    let path = TempPath::new("too-long");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let longest = "n".repeat(PROPERTY_NAME_MAX_SIZE);
+    let too_long = "n".repeat(PROPERTY_NAME_MAX_SIZE + 1);
+
+    assert!(matches!(engine.intern(&too_long).await, Err(StorageError::NameTooLong(name)) if name == too_long));
+    assert!(engine.name_table.is_empty());
+    let id = engine.intern(&longest).await.unwrap();
+    assert_eq!(engine.resolve(id).unwrap(), longest);
+}
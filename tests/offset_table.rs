@@ -0,0 +1,203 @@
+use std::fs::OpenOptions;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+use nexora_rs::models::file_layout::{
+    stamp_page_checksum, NexoraFooter, Node, OffsetItem, Section, HEADER_SLOT_OFFSETS, INVALID_OFFSET,
+    OFFSET_ITEMS_PER_CHUNK,
+};
+use nexora_rs::models::schema_builder::builder::NodeSchemaBuilder;
+use nexora_rs::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+
+mod common;
+use common::log::changed_pages;
+use common::TempPath;
+
+/// Enough nodes to fill the base chunk of the node table and start a second one.
+const NODES: u64 = OFFSET_ITEMS_PER_CHUNK as u64 + 46;
+
+/// A fresh engine at a path unique to the test, holding nodes 1 to `NODES`.
+async fn filled_engine(name: &str) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    for id in 1..=NODES {
+        engine.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    (path, engine)
+}
+
+/// Offsets and item counts of the chunks of `section`, in chain order.
+async fn chunks(engine: &StorageEngine, section: Section) -> Vec<(u64, u8)> {
+    let mut chunks = Vec::new();
+    let mut offset = engine.file_layout.footer.table(section).base_chunk_offset;
+    while offset != INVALID_OFFSET {
+        let chunk = engine.read_offset_table(offset).await.unwrap();
+        chunks.push((offset, chunk.nb_items));
+        offset = chunk.next_chunk;
+    }
+    chunks
+}
+
+/// Item counts of the node table's chunks, in chain order.
+async fn chunk_sizes(engine: &StorageEngine) -> Vec<u8> {
+    chunks(engine, Section::Nodes).await.into_iter().map(|(_, size)| size).collect()
+}
+
+/// Metadata items `ids`, pointing at ten times their id.
+fn metadata_items(ids: std::ops::RangeInclusive<u64>) -> Vec<OffsetItem> {
+    ids.map(|id| OffsetItem { id, offset: id * 10 }).collect()
+}
+
+async fn sorted_node_ids(engine: &StorageEngine) -> Vec<u64> {
+    let mut ids: Vec<u64> = engine.scan_nodes(..).await.unwrap().iter().map(|node| node.id).collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// Rewrites the current footer of the file at `path` after passing it through `patch`.
+fn patch_footer(path: &Path, engine: &StorageEngine, patch: impl FnOnce(&mut NexoraFooter)) {
+    let mut footer = engine.file_layout.footer;
+    patch(&mut footer);
+    let mut page = footer.serialize();
+    stamp_page_checksum(&mut page);
+    let file = OpenOptions::new().write(true).open(path).unwrap();
+    file.write_all_at(&page, engine.file_layout.header.footer_offset).unwrap();
+}
+
+#[tokio::test]
+async fn removals_keep_every_chunk_but_the_last_full() {
+    let (_, mut engine) = filled_engine("compaction").await;
+    assert_eq!(chunk_sizes(&engine).await, [OFFSET_ITEMS_PER_CHUNK as u8, 46]);
+
+    // Holes in the base chunk are filled from the tail chunk.
+    for id in 1..=10 {
+        engine.delete_node(id).await.unwrap();
+    }
+    assert_eq!(chunk_sizes(&engine).await, [OFFSET_ITEMS_PER_CHUNK as u8, 36]);
+    assert_eq!(engine.file_layout.footer.table(Section::Nodes).nb_total_items, NODES - 10);
+    assert_eq!(sorted_node_ids(&engine).await, (11..=NODES).collect::<Vec<_>>());
+    assert!(engine.find_offset_item(Section::Nodes, 5).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn emptied_chunks_are_unlinked_and_freed() {
+    let (path, mut engine) = filled_engine("unlink").await;
+    let base = engine.file_layout.footer.table(Section::Nodes).base_chunk_offset;
+    let tail = engine.file_layout.footer.tail_chunk(Section::Nodes);
+
+    for id in 1..=46 {
+        engine.delete_node(id).await.unwrap();
+    }
+    assert_eq!(chunk_sizes(&engine).await, [OFFSET_ITEMS_PER_CHUNK as u8]);
+    assert_eq!(engine.file_layout.footer.tail_chunk(Section::Nodes), base);
+    assert_eq!(engine.file_layout.footer.free_page_head, tail);
+
+    // The base chunk stays linked even once empty.
+    for id in 47..=NODES {
+        engine.delete_node(id).await.unwrap();
+    }
+    assert_eq!(chunk_sizes(&engine).await, [0]);
+    assert_eq!(engine.file_layout.footer.tail_chunk(Section::Nodes), base);
+    drop(engine);
+
+    let mut engine = StorageEngine::load(&path).await.unwrap();
+    assert!(engine.scan_nodes(..).await.unwrap().is_empty());
+    engine.insert_node(&Node { id: 1, schema_id: 1, ..Default::default() }).await.unwrap();
+    assert_eq!(chunk_sizes(&engine).await, [1]);
+}
+
+#[tokio::test]
+async fn stale_tail_pointers_are_fixed_on_load() {
+    let (path, engine) = filled_engine("stale-tail").await;
+    let tail = engine.file_layout.footer.tail_chunk(Section::Nodes);
+    let base = engine.file_layout.footer.table(Section::Nodes).base_chunk_offset;
+    assert_ne!(tail, base);
+    patch_footer(&path, &engine, |footer| footer.set_tail_chunk(Section::Nodes, base));
+    drop(engine);
+
+    let mut engine = StorageEngine::load(&path).await.unwrap();
+    assert_eq!(engine.file_layout.footer.tail_chunk(Section::Nodes), tail);
+    engine.insert_node(&Node { id: NODES + 1, schema_id: 1, ..Default::default() }).await.unwrap();
+    assert_eq!(chunk_sizes(&engine).await, [OFFSET_ITEMS_PER_CHUNK as u8, 47]);
+}
+
+#[tokio::test]
+async fn miscounted_tables_fail_removals() {
+    let (path, engine) = filled_engine("miscounted").await;
+    patch_footer(&path, &engine, |footer| footer.table_mut(Section::Nodes).nb_total_items = 0);
+    drop(engine);
+
+    let mut engine = StorageEngine::load(&path).await.unwrap();
+    assert!(matches!(
+        engine.delete_node(1).await,
+        Err(StorageError::Corrupted(CorruptedFileError::ItemCountMismatch(Section::Nodes)))
+    ));
+    assert_eq!(sorted_node_ids(&engine).await, (1..=NODES).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn indexed_sections_only_change_through_their_records() {
+    let (_, mut engine) = filled_engine("indexed").await;
+    let item = OffsetItem { id: NODES + 1, offset: 0 };
+
+    for section in [Section::Nodes, Section::Edges] {
+        assert!(matches!(engine.insert_offset_item(section, item).await, Err(StorageError::Indexed(s)) if s == section));
+        assert!(matches!(engine.insert_offset_items(section, &[item]).await, Err(StorageError::Indexed(s)) if s == section));
+        assert!(matches!(engine.remove_offset_item(section, 1).await, Err(StorageError::Indexed(s)) if s == section));
+    }
+    assert_eq!(engine.file_layout.footer.table(Section::Nodes).nb_total_items, NODES);
+    assert_eq!(engine.get_node(1).await.unwrap().unwrap().id, 1);
+}
+
+#[tokio::test]
+async fn batches_fill_the_tail_chunk_then_chain_new_ones() {
+    let path = TempPath::new("batch");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    for item in metadata_items(1..=10) {
+        engine.insert_offset_item(Section::Metadata, item).await.unwrap();
+    }
+    let lsn = engine.snapshot().await.unwrap().lsn();
+    let before = std::fs::read(&path).unwrap();
+
+    let count = 2 * OFFSET_ITEMS_PER_CHUNK as u64 + 20;
+    engine.insert_offset_items(Section::Metadata, &metadata_items(11..=10 + count)).await.unwrap();
+    let sizes: Vec<u8> = chunks(&engine, Section::Metadata).await.iter().map(|(_, size)| *size).collect();
+    assert_eq!(sizes, [OFFSET_ITEMS_PER_CHUNK as u8, OFFSET_ITEMS_PER_CHUNK as u8, 30]);
+    assert_eq!(engine.file_layout.footer.table(Section::Metadata).nb_total_items, 10 + count);
+    for id in [1, 10, 11, 254, 255, 509, 10 + count] {
+        let (_, _, item) = engine.find_offset_item(Section::Metadata, id).await.unwrap().unwrap();
+        assert_eq!(item.offset, id * 10);
+    }
+
+    // The whole batch commits at once. Past the header and the pages the file grew by, it
+    // only rewrites the footer and the chunks it filled.
+    assert_eq!(engine.snapshot().await.unwrap().lsn(), lsn + 1);
+    let mut changed: Vec<u64> = changed_pages(&before, &std::fs::read(&path).unwrap())
+        .into_iter()
+        .filter(|(offset, page)| !HEADER_SLOT_OFFSETS.contains(offset) && page.iter().any(|byte| *byte != 0))
+        .map(|(offset, _)| offset)
+        .collect();
+    changed.sort_unstable();
+    let mut expected: Vec<u64> = chunks(&engine, Section::Metadata).await.iter().map(|(offset, _)| *offset).collect();
+    expected.push(engine.file_layout.header.footer_offset);
+    expected.sort_unstable();
+    assert_eq!(changed, expected);
+}
+
+#[tokio::test]
+async fn tail_pointers_survive_reopening() {
+    let path = TempPath::new("tail-reopen");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let count = OFFSET_ITEMS_PER_CHUNK as u64 + 5;
+    engine.insert_offset_items(Section::Metadata, &metadata_items(1..=count)).await.unwrap();
+    let tail = engine.file_layout.footer.tail_chunk(Section::Metadata);
+    assert_eq!(chunks(&engine, Section::Metadata).await.last(), Some(&(tail, 5)));
+    drop(engine);
+
+    let mut engine = StorageEngine::load(&path).await.unwrap();
+    assert_eq!(engine.file_layout.footer.tail_chunk(Section::Metadata), tail);
+    engine.insert_offset_items(Section::Metadata, &metadata_items(count + 1..=count + 3)).await.unwrap();
+    assert_eq!(chunks(&engine, Section::Metadata).await.last(), Some(&(tail, 8)));
+    assert_eq!(engine.file_layout.footer.table(Section::Metadata).nb_total_items, count + 3);
+}
@@ -0,0 +1,66 @@
+use std::io::ErrorKind;
+
+use nexora_rs::models::file_layout::Node;
+use nexora_rs::models::schema_builder::builder::NodeSchemaBuilder;
+use nexora_rs::storage_engine::engine::{StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+fn is_io_error(result: Result<StorageEngine, StorageError>, kind: ErrorKind) -> bool {
+    matches!(result, Err(StorageError::Io(err)) if err.kind() == kind)
+}
+
+#[tokio::test]
+async fn create_new_refuses_existing_files() {
+    let path = TempPath::new("create-new");
+    let mut engine = StorageEngine::options().create_new(true).open(&path).await.unwrap();
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    drop(engine);
+    let before = std::fs::read(&path).unwrap();
+
+    assert!(is_io_error(StorageEngine::options().create_new(true).open(&path).await, ErrorKind::AlreadyExists));
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+    let engine = StorageEngine::options().create(true).open(&path).await.unwrap();
+    assert!(engine.get_node_schema(1).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn read_only_engines_refuse_writes() {
+    let path = TempPath::new("read-only");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    engine.insert_node(&Node { id: 1, schema_id: 1, ..Default::default() }).await.unwrap();
+    drop(engine);
+    let before = std::fs::read(&path).unwrap();
+
+    let mut engine = StorageEngine::options().read_only(true).open(&path).await.unwrap();
+    assert_eq!(engine.get_node(1).await.unwrap().unwrap().id, 1);
+    let node = Node { id: 2, schema_id: 1, ..Default::default() };
+    assert!(matches!(engine.insert_node(&node).await, Err(StorageError::ReadOnly)));
+    assert!(matches!(engine.delete_node(1).await, Err(StorageError::ReadOnly)));
+    assert!(matches!(engine.register_node_schema(&NodeSchemaBuilder::new(2)).await, Err(StorageError::ReadOnly)));
+    assert!(matches!(engine.intern("name").await, Err(StorageError::ReadOnly)));
+    assert!(matches!(engine.begin().await, Err(StorageError::ReadOnly)));
+    drop(engine);
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+
+    // Read-only engines cannot create files either.
+    let result = StorageEngine::options().read_only(true).create(true).open(&path).await;
+    assert!(is_io_error(result, ErrorKind::InvalidInput));
+}
+
+#[tokio::test]
+async fn missing_files_are_only_created_on_request() {
+    let path = TempPath::new("missing");
+
+    assert!(is_io_error(StorageEngine::options().open(&path).await, ErrorKind::NotFound));
+    assert!(is_io_error(StorageEngine::load(&path).await, ErrorKind::NotFound));
+    assert!(is_io_error(StorageEngine::options().read_only(true).open(&path).await, ErrorKind::NotFound));
+    assert!(!path.exists());
+
+    let engine = StorageEngine::options().create(true).open(&path).await.unwrap();
+    assert!(engine.scan_nodes(..).await.unwrap().is_empty());
+    drop(engine);
+    StorageEngine::options().open(&path).await.unwrap();
+}
@@ -0,0 +1,134 @@
+use nexora_rs::models::file_layout::{Edge, Node, PropertyType};
+use nexora_rs::models::property_value::PropertyValue;
+use nexora_rs::models::schema_builder::builder::{EdgeSchemaBuilder, NodeSchemaBuilder, PropertyBuilder};
+use nexora_rs::storage_engine::engine::{StorageEngine, StorageError};
+use nexora_rs::utils::encoding::float::float::{F16, F8E4M3};
+
+mod common;
+use common::TempPath;
+
+/// One optional property of each type, named after it.
+const PROPERTIES: [(&str, PropertyType); 10] = [
+    ("bool", PropertyType::Bool),
+    ("float8", PropertyType::Float8),
+    ("float16", PropertyType::Float16),
+    ("float32", PropertyType::Float32),
+    ("float64", PropertyType::Float64),
+    ("int64", PropertyType::Int64),
+    ("string32", PropertyType::String32),
+    ("string64", PropertyType::String64),
+    ("string512", PropertyType::String512),
+    ("page", PropertyType::Page),
+];
+
+/// A fresh engine at a path unique to the test, with node schema 1 and edge schema 1 both
+/// holding every property of `PROPERTIES`.
+async fn fresh_engine(name: &str) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let mut node_schema = NodeSchemaBuilder::new(1);
+    let mut edge_schema = EdgeSchemaBuilder::new(1);
+    for (name, property_type) in PROPERTIES {
+        node_schema = node_schema.property(PropertyBuilder::new(name.to_string(), property_type, true));
+        edge_schema = edge_schema.property(PropertyBuilder::new(name.to_string(), property_type, true));
+    }
+    engine.register_node_schema(&node_schema).await.unwrap();
+    engine.register_edge_schema(&edge_schema).await.unwrap();
+    (path, engine)
+}
+
+/// A value for each property of `PROPERTIES`.
+fn sample_values() -> Vec<(&'static str, PropertyValue)> {
+    vec![
+        ("bool", PropertyValue::Bool(true)),
+        ("float8", PropertyValue::Float8(F8E4M3::from_f32(-1.5))),
+        ("float16", PropertyValue::Float16(F16::from_f32(0.333))),
+        ("float32", PropertyValue::Float32(-1234.5678)),
+        ("float64", PropertyValue::Float64(std::f64::consts::PI)),
+        ("int64", PropertyValue::Int64(i64::MIN)),
+        ("string32", PropertyValue::String("héllo".to_string())),
+        ("string64", PropertyValue::String("s".repeat(64))),
+        ("string512", PropertyValue::String("ünïcode ".repeat(40))),
+        ("page", PropertyValue::Bytes((0..4000).map(|byte| (byte % 251) as u8).collect())),
+    ]
+}
+
+#[tokio::test]
+async fn node_properties_round_trip() {
+    let (path, mut engine) = fresh_engine("nodes").await;
+    let mut node = Node { id: 1, schema_id: 1, ..Default::default() };
+    for (name, value) in sample_values() {
+        node.set(&mut engine, name, value).await.unwrap();
+    }
+    engine.insert_node(&node).await.unwrap();
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    let node = engine.get_node(1).await.unwrap().unwrap();
+    for (name, value) in sample_values() {
+        assert_eq!(node.get(&engine, name).await.unwrap(), value, "{name}");
+    }
+}
+
+#[tokio::test]
+async fn edge_properties_round_trip() {
+    let (path, mut engine) = fresh_engine("edges").await;
+    engine.insert_node(&Node { id: 1, schema_id: 1, ..Default::default() }).await.unwrap();
+    let mut edge = Edge { id: 1, schema_id: 1, source_id: 1, destination_id: 1, ..Default::default() };
+    for (name, value) in sample_values() {
+        edge.set(&mut engine, name, value).await.unwrap();
+    }
+    engine.insert_edge(&edge).await.unwrap();
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    let edge = engine.get_edge(1).await.unwrap().unwrap();
+    for (name, value) in sample_values() {
+        assert_eq!(edge.get(&engine, name).await.unwrap(), value, "{name}");
+    }
+}
+
+#[tokio::test]
+async fn null_clears_a_property() {
+    let (_, mut engine) = fresh_engine("null").await;
+    let mut node = Node { id: 1, schema_id: 1, ..Default::default() };
+    node.set(&mut engine, "bool", PropertyValue::Bool(false)).await.unwrap();
+    node.set(&mut engine, "string32", PropertyValue::String("gone".to_string())).await.unwrap();
+    assert_eq!(node.get(&engine, "bool").await.unwrap(), PropertyValue::Bool(false));
+
+    node.set(&mut engine, "bool", PropertyValue::Null).await.unwrap();
+    node.set(&mut engine, "string32", PropertyValue::Null).await.unwrap();
+    engine.insert_node(&node).await.unwrap();
+    let node = engine.get_node(1).await.unwrap().unwrap();
+    assert_eq!(node.get(&engine, "bool").await.unwrap(), PropertyValue::Null);
+    assert_eq!(node.get(&engine, "string32").await.unwrap(), PropertyValue::Null);
+}
+
+#[tokio::test]
+async fn values_of_another_type_are_refused() {
+    let (_, mut engine) = fresh_engine("mismatch").await;
+    let mut node = Node { id: 1, schema_id: 1, ..Default::default() };
+    let mismatches = [
+        ("bool", PropertyValue::Int64(1), PropertyType::Bool),
+        ("float32", PropertyValue::Float64(1.0), PropertyType::Float32),
+        ("float8", PropertyValue::Float16(F16::from_f32(1.0)), PropertyType::Float8),
+        ("int64", PropertyValue::String("1".to_string()), PropertyType::Int64),
+        ("string32", PropertyValue::Bytes(b"bytes".to_vec()), PropertyType::String32),
+        ("page", PropertyValue::String("text".to_string()), PropertyType::Page),
+    ];
+
+    for (name, value, expected) in mismatches {
+        match node.set(&mut engine, name, value).await {
+            Err(StorageError::TypeMismatch { property, property_type }) => {
+                assert_eq!(property, name);
+                assert_eq!(property_type, expected);
+            }
+            other => panic!("expected a mismatch on {name:?}, got {other:?}"),
+        }
+    }
+    assert!(node.present.iter().all(|byte| *byte == 0));
+    assert!(matches!(
+        node.get(&engine, "missing").await,
+        Err(StorageError::UnknownProperty(name)) if name == "missing"
+    ));
+}
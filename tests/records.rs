@@ -0,0 +1,133 @@
+
+use nexora_rs::models::file_layout::{Edge, Node, PropertyType, RECORDS_PER_PAGE};
+use nexora_rs::models::property_value::PropertyValue;
+use nexora_rs::models::schema_builder::builder::{EdgeSchemaBuilder, NodeSchemaBuilder, PropertyBuilder};
+use nexora_rs::storage_engine::engine::{StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+const NODES: u64 = 300;
+
+/// A fresh engine at a path unique to the test, with a node schema holding an `Int64` weight
+/// and an edge schema holding an `Int64` cost.
+async fn fresh_engine(name: &str) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let weight = PropertyBuilder::new("weight".to_string(), PropertyType::Int64, true);
+    engine.register_node_schema(&NodeSchemaBuilder::new(1).property(weight)).await.unwrap();
+    let cost = PropertyBuilder::new("cost".to_string(), PropertyType::Int64, true);
+    engine.register_edge_schema(&EdgeSchemaBuilder::new(1).property(cost)).await.unwrap();
+    (path, engine)
+}
+
+async fn insert_weighted(engine: &mut StorageEngine, ids: impl Iterator<Item = u64>) {
+    for id in ids {
+        let mut node = Node { id, schema_id: 1, ..Default::default() };
+        node.set(engine, "weight", PropertyValue::Int64(id as i64 * 3)).await.unwrap();
+        engine.insert_node(&node).await.unwrap();
+    }
+}
+
+/// Edge `id` of schema 1 from `source_id` to `destination_id`, costing `cost`.
+async fn edge(engine: &mut StorageEngine, id: u64, (source_id, destination_id): (u64, u64), cost: i64) -> Edge {
+    let mut edge = Edge { id, schema_id: 1, source_id, destination_id, ..Default::default() };
+    edge.set(engine, "cost", PropertyValue::Int64(cost)).await.unwrap();
+    edge
+}
+
+async fn edge_summary(engine: &StorageEngine, id: u64) -> Option<(u64, u64, PropertyValue)> {
+    let edge = engine.get_edge(id).await.unwrap()?;
+    assert_eq!((edge.id, edge.schema_id), (id, 1));
+    Some((edge.source_id, edge.destination_id, edge.get(engine, "cost").await.unwrap()))
+}
+
+#[tokio::test]
+async fn records_share_pages() {
+    let (_, mut engine) = fresh_engine("share").await;
+    let before = engine.page_stats().used_pages;
+    insert_weighted(&mut engine, 1..=NODES).await;
+
+    // Record pages, plus a couple of offset table chunks and index nodes.
+    let record_pages = NODES.div_ceil(RECORDS_PER_PAGE as u64);
+    let used = engine.page_stats().used_pages - before;
+    assert!(used <= record_pages + 6, "{NODES} nodes took {used} pages");
+
+    for id in 1..=NODES {
+        let node = engine.get_node(id).await.unwrap().unwrap();
+        assert_eq!(node.get(&engine, "weight").await.unwrap(), PropertyValue::Int64(id as i64 * 3));
+    }
+}
+
+#[tokio::test]
+async fn freed_slots_are_reused() {
+    let (path, mut engine) = fresh_engine("reuse").await;
+    insert_weighted(&mut engine, 1..=NODES).await;
+    let total = engine.page_stats().total_pages;
+
+    for id in (1..=NODES).step_by(2) {
+        engine.delete_node(id).await.unwrap();
+    }
+    for id in (2..=NODES).step_by(2) {
+        let node = engine.get_node(id).await.unwrap().unwrap();
+        assert_eq!(node.get(&engine, "weight").await.unwrap(), PropertyValue::Int64(id as i64 * 3));
+    }
+
+    insert_weighted(&mut engine, NODES + 1..=NODES + NODES / 2).await;
+    assert_eq!(engine.page_stats().total_pages, total);
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    let ids: Vec<u64> = engine.scan_nodes(..).await.unwrap().iter().map(|node| node.id).collect();
+    let expected: Vec<u64> = (2..=NODES).step_by(2).chain(NODES + 1..=NODES + NODES / 2).collect();
+    assert_eq!(ids, expected);
+}
+
+#[tokio::test]
+async fn edges_are_inserted_updated_and_deleted() {
+    let (path, mut engine) = fresh_engine("edges").await;
+    insert_weighted(&mut engine, 1..=3).await;
+    let first = edge(&mut engine, 1, (1, 2), 5).await;
+    engine.insert_edge(&first).await.unwrap();
+    let second = edge(&mut engine, 2, (2, 3), 7).await;
+    engine.insert_edge(&second).await.unwrap();
+    assert_eq!(edge_summary(&engine, 1).await, Some((1, 2, PropertyValue::Int64(5))));
+    assert!(matches!(engine.insert_edge(&first).await, Err(StorageError::AlreadyExists(1))));
+
+    let rerouted = edge(&mut engine, 1, (3, 1), 9).await;
+    engine.update_edge(&rerouted).await.unwrap();
+    assert_eq!(edge_summary(&engine, 1).await, Some((3, 1, PropertyValue::Int64(9))));
+    drop(engine);
+
+    let mut engine = StorageEngine::load(&path).await.unwrap();
+    assert_eq!(edge_summary(&engine, 1).await, Some((3, 1, PropertyValue::Int64(9))));
+    assert_eq!(edge_summary(&engine, 2).await, Some((2, 3, PropertyValue::Int64(7))));
+    engine.delete_edge(1).await.unwrap();
+    assert_eq!(edge_summary(&engine, 1).await, None);
+    let ids: Vec<u64> = engine.scan_edges(..).await.unwrap().iter().map(|edge| edge.id).collect();
+    assert_eq!(ids, [2]);
+
+    assert!(matches!(engine.delete_edge(1).await, Err(StorageError::NotFound(1))));
+    assert!(matches!(engine.update_edge(&rerouted).await, Err(StorageError::NotFound(1))));
+    assert!(engine.get_node(1).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn edges_need_both_endpoints() {
+    let (_, mut engine) = fresh_engine("dangling").await;
+    insert_weighted(&mut engine, 1..=2).await;
+
+    let from_missing = edge(&mut engine, 1, (9, 1), 0).await;
+    assert!(matches!(engine.insert_edge(&from_missing).await, Err(StorageError::DanglingEdge(9))));
+    let to_missing = edge(&mut engine, 1, (1, 8), 0).await;
+    assert!(matches!(engine.insert_edge(&to_missing).await, Err(StorageError::DanglingEdge(8))));
+    assert!(engine.get_edge(1).await.unwrap().is_none());
+
+    // Updates cannot leave an edge dangling either.
+    let linked = edge(&mut engine, 1, (1, 2), 0).await;
+    engine.insert_edge(&linked).await.unwrap();
+    engine.delete_node(2).await.unwrap();
+    let repriced = edge(&mut engine, 1, (1, 2), 4).await;
+    assert!(matches!(engine.update_edge(&repriced).await, Err(StorageError::DanglingEdge(2))));
+    assert_eq!(edge_summary(&engine, 1).await, Some((1, 2, PropertyValue::Int64(0))));
+}
@@ -0,0 +1,97 @@
+use nexora_rs::models::file_layout::{PropertyType, MAX_PROPERTIES_COUNT};
+use nexora_rs::models::schema_builder::builder::{EdgeSchemaBuilder, NodeSchemaBuilder, PropertyBuilder};
+use nexora_rs::storage_engine::engine::{StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+fn property(name: &str, property_type: PropertyType, optional: bool) -> PropertyBuilder {
+    PropertyBuilder::new(name.to_string(), property_type, optional)
+}
+
+/// Names, types and optionality of `properties`, in order.
+fn described(properties: &[PropertyBuilder]) -> Vec<(String, PropertyType, bool)> {
+    properties
+        .iter()
+        .map(|property| (property.name().to_string(), property.property_type(), property.is_optional()))
+        .collect()
+}
+
+#[tokio::test]
+async fn node_schemas_load_back_after_reopening() {
+    let path = TempPath::new("node-schema");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let schema = NodeSchemaBuilder::new(7)
+        .property(property("name", PropertyType::String64, false))
+        .property(property("age", PropertyType::Int16, true))
+        .property(property("score", PropertyType::Float32, true));
+    assert_eq!(engine.register_node_schema(&schema).await.unwrap(), 7);
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    let loaded = engine.load_node_schema(7).await.unwrap().unwrap();
+    assert_eq!(loaded.id, 7);
+    assert_eq!(described(&loaded.properties), described(&schema.properties));
+
+    let stored = engine.get_node_schema(7).await.unwrap().unwrap();
+    assert_eq!(stored.property_count, 3);
+    let definition = engine.get_property_definition(stored.properties[1]).await.unwrap().unwrap();
+    assert_eq!(engine.resolve(definition.name_id).unwrap(), "age");
+    assert_eq!(definition.r#type, PropertyType::Int16);
+    assert!(engine.load_node_schema(8).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn edge_schemas_are_kept_apart_from_node_schemas() {
+    let path = TempPath::new("edge-schema");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let schema = EdgeSchemaBuilder::new(3)
+        .property(property("since", PropertyType::Int64, false))
+        .property(property("label", PropertyType::String32, true));
+    assert_eq!(engine.register_edge_schema(&schema).await.unwrap(), 3);
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    let loaded = engine.load_edge_schema(3).await.unwrap().unwrap();
+    assert_eq!(loaded.id, 3);
+    assert_eq!(described(&loaded.properties), described(&schema.properties));
+    assert!(engine.get_node_schema(3).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn duplicate_schema_ids_are_refused() {
+    let path = TempPath::new("duplicate");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    let schemas = engine.file_layout.footer.node_schema_offset.nb_total_items;
+
+    let schema = NodeSchemaBuilder::new(1).property(property("other", PropertyType::Bool, true));
+    assert!(matches!(engine.register_node_schema(&schema).await, Err(StorageError::AlreadyExists(1))));
+    assert!(engine.name_table.lookup("other").is_none());
+    assert_eq!(engine.file_layout.footer.node_schema_offset.nb_total_items, schemas);
+    assert!(engine.load_node_schema(1).await.unwrap().unwrap().properties.is_empty());
+
+    // Node and edge schema ids are counted separately.
+    engine.register_edge_schema(&EdgeSchemaBuilder::new(1)).await.unwrap();
+}
+
+#[tokio::test]
+async fn schemas_hold_at_most_max_properties() {
+    let path = TempPath::new("too-many");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let mut schema = NodeSchemaBuilder::new(1);
+    schema.properties = (0..=MAX_PROPERTIES_COUNT)
+        .map(|index| property(&format!("p{index}"), PropertyType::Int8, true))
+        .collect();
+
+    assert!(matches!(
+        engine.register_node_schema(&schema).await,
+        Err(StorageError::TooManyProperties(count)) if count == MAX_PROPERTIES_COUNT + 1
+    ));
+    assert!(engine.get_node_schema(1).await.unwrap().is_none());
+
+    schema.properties.pop();
+    engine.register_node_schema(&schema).await.unwrap();
+    let loaded = engine.load_node_schema(1).await.unwrap().unwrap();
+    assert_eq!(loaded.properties.len(), MAX_PROPERTIES_COUNT);
+}
@@ -0,0 +1,129 @@
+use nexora_rs::models::file_layout::{Edge, Node, PropertyType, PAGE_SIZE, PAGE_TRAILER_SIZE};
+use nexora_rs::models::property_value::PropertyValue;
+use nexora_rs::models::schema_builder::builder::{EdgeSchemaBuilder, NodeSchemaBuilder, PropertyBuilder};
+use nexora_rs::storage_engine::engine::{CorruptedFileError, StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+/// A fresh engine at a path unique to the test, holding nodes 1 and 2 of a schema with a
+/// `String32` name, and edge schema 1.
+async fn fresh_engine(name: &str) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let name = PropertyBuilder::new("name".to_string(), PropertyType::String32, true);
+    engine.register_node_schema(&NodeSchemaBuilder::new(1).property(name)).await.unwrap();
+    engine.register_edge_schema(&EdgeSchemaBuilder::new(1)).await.unwrap();
+    for id in 1..=2 {
+        let mut node = Node { id, schema_id: 1, ..Default::default() };
+        node.set(&mut engine, "name", PropertyValue::String(format!("node {id}"))).await.unwrap();
+        engine.insert_node(&node).await.unwrap();
+    }
+    (path, engine)
+}
+
+fn edge(id: u64, source_id: u64, destination_id: u64) -> Edge {
+    Edge { id, schema_id: 1, source_id, destination_id, ..Default::default() }
+}
+
+/// The footer as it is written, leaving out the checksum trailer a loaded footer keeps.
+fn footer_image(engine: &StorageEngine) -> Vec<u8> {
+    engine.file_layout.footer.serialize()[..PAGE_SIZE - PAGE_TRAILER_SIZE].to_vec()
+}
+
+/// Inserts node 3 and edge 1, and deletes node 2, through `engine`.
+async fn make_changes(engine: &mut StorageEngine) {
+    engine.insert_node(&Node { id: 3, schema_id: 1, ..Default::default() }).await.unwrap();
+    engine.insert_edge(&edge(1, 1, 3)).await.unwrap();
+    engine.delete_node(2).await.unwrap();
+}
+
+/// Checks that `engine` holds nodes 1 and 2 and no edge, as before `make_changes`.
+async fn assert_unchanged(engine: &StorageEngine) {
+    let ids: Vec<u64> = engine.scan_nodes(..).await.unwrap().iter().map(|node| node.id).collect();
+    assert_eq!(ids, [1, 2]);
+    assert!(engine.get_edge(1).await.unwrap().is_none());
+    let node = engine.get_node(2).await.unwrap().unwrap();
+    assert_eq!(node.get(engine, "name").await.unwrap(), PropertyValue::String("node 2".to_string()));
+}
+
+#[tokio::test]
+async fn rollbacks_leave_records_and_footer_unchanged() {
+    let (path, mut engine) = fresh_engine("rollback").await;
+    let footer = footer_image(&engine);
+
+    let mut transaction = engine.begin().await.unwrap();
+    make_changes(&mut transaction).await;
+    assert!(transaction.get_node(3).await.unwrap().is_some());
+    transaction.rollback();
+
+    assert_unchanged(&engine).await;
+    assert_eq!(footer_image(&engine), footer);
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    assert_unchanged(&engine).await;
+    assert_eq!(footer_image(&engine), footer);
+}
+
+#[tokio::test]
+async fn dropped_transactions_roll_back() {
+    let (path, mut engine) = fresh_engine("drop").await;
+    let footer = footer_image(&engine);
+
+    let mut transaction = engine.begin().await.unwrap();
+    make_changes(&mut transaction).await;
+    drop(transaction);
+
+    assert_unchanged(&engine).await;
+    assert_eq!(footer_image(&engine), footer);
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    assert_unchanged(&engine).await;
+    assert_eq!(footer_image(&engine), footer);
+}
+
+#[tokio::test]
+async fn failed_operations_abort_the_transaction() {
+    let (_, mut engine) = fresh_engine("aborted").await;
+    let footer = footer_image(&engine);
+    // Freeing the name of node 1 behind its back makes deleting it fail once the record
+    // is gone.
+    let name = engine.get_node(1).await.unwrap().unwrap().property_values[0];
+    engine.free_heap_value(PropertyType::String32, name).await.unwrap();
+    let footer_after_free = footer_image(&engine);
+    assert_ne!(footer_after_free, footer);
+
+    let mut transaction = engine.begin().await.unwrap();
+    transaction.insert_node(&Node { id: 3, schema_id: 1, ..Default::default() }).await.unwrap();
+    assert!(matches!(
+        transaction.delete_node(1).await,
+        Err(StorageError::Corrupted(CorruptedFileError::InvalidOffsetValue))
+    ));
+    assert!(matches!(transaction.commit().await, Err(StorageError::TransactionAborted)));
+
+    assert!(engine.get_node(3).await.unwrap().is_none());
+    assert!(engine.get_node(1).await.unwrap().is_some());
+    assert_eq!(footer_image(&engine), footer_after_free);
+}
+
+#[tokio::test]
+async fn commits_are_durable_together() {
+    let (path, mut engine) = fresh_engine("commit").await;
+    let footer = footer_image(&engine);
+
+    let mut transaction = engine.begin().await.unwrap();
+    make_changes(&mut transaction).await;
+    transaction.commit().await.unwrap();
+    let committed = footer_image(&engine);
+    assert_ne!(committed, footer);
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    let ids: Vec<u64> = engine.scan_nodes(..).await.unwrap().iter().map(|node| node.id).collect();
+    assert_eq!(ids, [1, 3]);
+    let stored = engine.get_edge(1).await.unwrap().unwrap();
+    assert_eq!((stored.source_id, stored.destination_id), (1, 3));
+    assert_eq!(footer_image(&engine), committed);
+}
@@ -0,0 +1,129 @@
+use nexora_rs::models::file_layout::{Edge, Node, PropertyType};
+use nexora_rs::models::schema_builder::builder::{EdgeSchemaBuilder, NodeSchemaBuilder, PropertyBuilder};
+use nexora_rs::storage_engine::engine::{StorageEngine, StorageError};
+
+mod common;
+use common::TempPath;
+
+/// A fresh engine at a path unique to the test, with node schema 1 holding a required
+/// `Int8` level and an optional `Bool` flag, and edge schema 1 holding a required `Int32`.
+async fn fresh_engine(name: &str) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let node_schema = NodeSchemaBuilder::new(1)
+        .property(PropertyBuilder::new("level".to_string(), PropertyType::Int8, false))
+        .property(PropertyBuilder::new("flag".to_string(), PropertyType::Bool, true));
+    engine.register_node_schema(&node_schema).await.unwrap();
+    let cost = PropertyBuilder::new("cost".to_string(), PropertyType::Int32, false);
+    let edge_schema = EdgeSchemaBuilder::new(1).property(cost);
+    engine.register_edge_schema(&edge_schema).await.unwrap();
+    (path, engine)
+}
+
+/// Node 1 of schema 1 with `level` set to `raw`.
+fn node_with_level(raw: u64) -> Node {
+    let mut node = Node { id: 1, schema_id: 1, ..Default::default() };
+    node.set_property(0, raw);
+    node
+}
+
+/// Asserts that `result` is a schema violation of `property` whose reason mentions `reason`.
+fn assert_violation(result: Result<(), StorageError>, property: &str, reason: &str) {
+    match result {
+        Err(StorageError::SchemaViolation { property: found, reason: found_reason }) => {
+            assert_eq!(found, property);
+            assert!(found_reason.contains(reason), "unexpected reason {found_reason:?}");
+        }
+        other => panic!("expected a violation of {property:?}, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn valid_records_are_accepted() {
+    let (_, mut engine) = fresh_engine("valid").await;
+    let mut node = node_with_level(-5i64 as u64);
+    node.set_property(1, 1);
+    engine.insert_node(&node).await.unwrap();
+    engine.insert_node(&Node { id: 2, ..node_with_level(7) }).await.unwrap();
+
+    let mut edge = Edge { id: 1, schema_id: 1, source_id: 1, destination_id: 2, ..Default::default() };
+    edge.set_property(0, i32::MIN as i64 as u64);
+    engine.insert_edge(&edge).await.unwrap();
+}
+
+#[tokio::test]
+async fn required_properties_must_be_present() {
+    let (_, mut engine) = fresh_engine("required").await;
+
+    let result = engine.insert_node(&Node { id: 1, schema_id: 1, ..Default::default() }).await;
+    assert_violation(result, "level", "required property is missing");
+    assert!(engine.get_node(1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn values_must_fit_their_type() {
+    let (_, mut engine) = fresh_engine("encoding").await;
+
+    assert_violation(engine.insert_node(&node_with_level(300)).await, "level", "not encodable as Int8");
+    let mut node = node_with_level(1);
+    node.set_property(1, 2);
+    assert_violation(engine.insert_node(&node).await, "flag", "not encodable as Bool");
+
+    // Updates are held to the same rules.
+    engine.insert_node(&node_with_level(1)).await.unwrap();
+    assert_violation(engine.update_node(&node_with_level(1 << 8)).await, "level", "not encodable as Int8");
+}
+
+#[tokio::test]
+async fn slots_past_the_schema_must_stay_empty() {
+    let (_, mut engine) = fresh_engine("extra-slots").await;
+
+    let mut node = node_with_level(1);
+    node.set_property(2, 0);
+    assert_violation(engine.insert_node(&node).await, "#2", "only declares 2 properties");
+    let mut node = node_with_level(1);
+    node.property_values[5] = 9;
+    assert_violation(engine.insert_node(&node).await, "#5", "only declares 2 properties");
+}
+
+#[tokio::test]
+async fn heap_properties_must_point_at_a_heap_cell() {
+    let path = TempPath::new("heap-offset");
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    let name = PropertyBuilder::new("name".to_string(), PropertyType::String32, false);
+    engine.register_node_schema(&NodeSchemaBuilder::new(1).property(name)).await.unwrap();
+
+    let mut node = Node { id: 1, schema_id: 1, ..Default::default() };
+    node.set_property(0, 3);
+    assert_violation(engine.insert_node(&node).await, "name", "not encodable as String32");
+}
+
+#[tokio::test]
+async fn invalid_property_types_are_violations() {
+    let (_, mut engine) = fresh_engine("invalid-type").await;
+    let broken = PropertyBuilder::new("broken".to_string(), PropertyType::InvalidType, true);
+    engine.register_node_schema(&NodeSchemaBuilder::new(2).property(broken)).await.unwrap();
+
+    let mut node = Node { id: 1, schema_id: 2, ..Default::default() };
+    node.set_property(0, 0);
+    assert_violation(engine.insert_node(&node).await, "broken", "invalid property type");
+}
+
+#[tokio::test]
+async fn edges_are_validated_against_their_schema() {
+    let (_, mut engine) = fresh_engine("edges").await;
+    engine.insert_node(&node_with_level(1)).await.unwrap();
+
+    let mut edge = Edge { id: 1, schema_id: 1, source_id: 1, destination_id: 1, ..Default::default() };
+    assert_violation(engine.insert_edge(&edge).await, "cost", "required property is missing");
+    edge.set_property(0, u32::MAX as u64);
+    assert_violation(engine.insert_edge(&edge).await, "cost", "not encodable as Int32");
+}
+
+#[tokio::test]
+async fn records_of_unknown_schemas_are_refused() {
+    let (_, mut engine) = fresh_engine("unknown-schema").await;
+
+    let result = engine.insert_node(&Node { id: 1, schema_id: 9, ..Default::default() }).await;
+    assert!(matches!(result, Err(StorageError::UnknownSchema(9))));
+}
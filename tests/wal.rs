@@ -0,0 +1,108 @@
+
+use nexora_rs::models::file_layout::{Node, PAGE_SIZE};
+use nexora_rs::models::schema_builder::builder::NodeSchemaBuilder;
+use nexora_rs::storage_engine::engine::StorageEngine;
+use nexora_rs::storage_engine::wal::wal_path;
+
+mod common;
+use common::log::{changed_pages, encode_batch};
+use common::TempPath;
+
+/// Images of the data file before and after a transaction inserting nodes 6 to 8 on top of
+/// nodes 1 to 5.
+async fn file_images(name: &str) -> (Vec<u8>, Vec<u8>) {
+    let path = TempPath::new(&format!("{name}-source"));
+    let mut engine = StorageEngine::create(&path).await.unwrap();
+    engine.register_node_schema(&NodeSchemaBuilder::new(1)).await.unwrap();
+    for id in 1..=5 {
+        engine.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    let before = std::fs::read(&path).unwrap();
+
+    let mut transaction = engine.begin().await.unwrap();
+    for id in 6..=8 {
+        transaction.insert_node(&Node { id, schema_id: 1, ..Default::default() }).await.unwrap();
+    }
+    transaction.commit().await.unwrap();
+    (before, std::fs::read(&path).unwrap())
+}
+
+/// Writes `data` and `log` at a fresh path and loads the engine from them.
+async fn load_with_log(name: &str, data: &[u8], log: &[u8]) -> (TempPath, StorageEngine) {
+    let path = TempPath::new(name);
+    std::fs::write(&path, data).unwrap();
+    std::fs::write(wal_path(&path), log).unwrap();
+    let engine = StorageEngine::load(&path).await.unwrap();
+    (path, engine)
+}
+
+async fn node_ids(engine: &StorageEngine) -> Vec<u64> {
+    engine.scan_nodes(..).await.unwrap().iter().map(|node| node.id).collect()
+}
+
+#[tokio::test]
+async fn committed_batches_are_redone_on_load() {
+    let (before, after) = file_images("redo").await;
+    let log = encode_batch(1, &changed_pages(&before, &after));
+
+    let (path, engine) = load_with_log("redo", &before, &log).await;
+    assert_eq!(node_ids(&engine).await, (1..=8).collect::<Vec<_>>());
+    assert_eq!(std::fs::metadata(wal_path(&path)).unwrap().len(), 0);
+    drop(engine);
+
+    let engine = StorageEngine::load(&path).await.unwrap();
+    assert_eq!(node_ids(&engine).await, (1..=8).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn torn_data_files_are_repaired_from_the_log() {
+    let (before, after) = file_images("torn-data").await;
+    let pages = changed_pages(&before, &after);
+
+    // The crash hit halfway through writing the batch back.
+    let mut torn = before.clone();
+    torn.resize(torn.len().max(after.len()), 0);
+    for (offset, page) in &pages[..pages.len() / 2] {
+        torn[*offset as usize..*offset as usize + PAGE_SIZE].copy_from_slice(page);
+    }
+
+    let (_, engine) = load_with_log("torn-data", &torn, &encode_batch(1, &pages)).await;
+    assert_eq!(node_ids(&engine).await, (1..=8).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn torn_batches_are_ignored() {
+    let (before, after) = file_images("torn-batch").await;
+    let pages = changed_pages(&before, &after);
+
+    // A second batch cut short by the crash, which would wipe every changed page.
+    let garbage: Vec<_> = pages.iter().map(|(offset, _)| (*offset, vec![0xa5u8; PAGE_SIZE])).collect();
+    let torn = encode_batch(2, &garbage);
+    let mut log = encode_batch(1, &pages);
+    log.extend_from_slice(&torn[..torn.len() / 2]);
+
+    let (_, engine) = load_with_log("torn-batch", &before, &log).await;
+    assert_eq!(node_ids(&engine).await, (1..=8).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn corrupted_batches_are_ignored() {
+    let (before, after) = file_images("corrupted-batch").await;
+    let mut log = encode_batch(1, &changed_pages(&before, &after));
+    log[100] ^= 0x01;
+
+    let (_, engine) = load_with_log("corrupted-batch", &before, &log).await;
+    assert_eq!(node_ids(&engine).await, (1..=5).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn read_only_engines_read_through_the_log() {
+    let (before, after) = file_images("read-only").await;
+    let path = TempPath::new("read-only");
+    std::fs::write(&path, &before).unwrap();
+    std::fs::write(wal_path(&path), encode_batch(1, &changed_pages(&before, &after))).unwrap();
+
+    let engine = StorageEngine::options().read_only(true).open(&path).await.unwrap();
+    assert_eq!(node_ids(&engine).await, (1..=8).collect::<Vec<_>>());
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+}